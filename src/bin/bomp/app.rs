@@ -1,15 +1,33 @@
-use crate::cli::{Bump, Changelog, RawBump};
+use crate::cli::{Bump, BumpOptions, Changelog, RawBump};
 use bomper::{
     changelog::generate_changelog_entry,
-    config::Config,
+    config::{
+        BuildMetadataConfig, CargoReplaceMode, CommitPolicy, Config, FileRules, FileTableData,
+        RuleMode, TagSelection,
+    },
     error::{Error, Result},
-    replacers::{cargo, file, search, simple, ReplacementBuilder, VersionReplacement},
-    versioning::{get_commits_between_tags, get_commits_since_tag, get_latest_tag, Commit, Tag},
+    glob,
+    replacers::{
+        capture, cargo, file, nix, npm, python, rules, search, selector, simple, yaml,
+        ContentReplacer, ReplacementBuilder, VersionReplacement,
+    },
+    versioning::{
+        build_metadata, create_tag, get_commits_between_tags, get_commits_since_tag,
+        get_latest_tag, Commit, Tag, TagTemplate, VersionScheme,
+    },
 };
 use console::{style, Style};
-use gix::refs::transaction::PreviousValue;
 use similar::{ChangeTag, TextDiff};
-use std::{fmt, io::Write, path::PathBuf, process::Command};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// The tag template used for packages that are versioned independently
+const PACKAGE_TAG_TEMPLATE: &str = "{package}@{version}";
 
 pub struct App {
     pub config: Config,
@@ -19,16 +37,59 @@ impl App {
     pub fn new(config: Config) -> App {
         App { config }
     }
+
+    fn scheme(&self) -> VersionScheme {
+        self.config.scheme.clone().unwrap_or_default()
+    }
+
+    /// The paths that commits must change to be included, preferring those given on the command
+    /// line over the configured ones.
+    fn paths<'a>(&'a self, paths: &'a [PathBuf]) -> &'a [PathBuf] {
+        if paths.is_empty() {
+            &self.config.paths
+        } else {
+            paths
+        }
+    }
 }
 
 impl App {
     pub fn bump(&self, opts: &Bump) -> Result<()> {
         let repo = gix::discover(".")?;
+        if self.config.cargo == Some(CargoReplaceMode::Independent) {
+            return self.bump_packages(&repo, opts);
+        }
 
-        let (tag, commits) = changelog_commits(&repo)?;
+        let template = self.config.tag_template()?;
+        let (tag, commits) = changelog_commits(
+            &repo,
+            &self.scheme(),
+            template.as_ref(),
+            self.paths(&opts.paths),
+            &self.config.commits,
+            self.config.tag_selection,
+        )?;
 
-        let increment = opts.options.determine_increment(&commits, &tag.version)?;
-        let new_tag = tag.increment_version(increment);
+        let Some(increment) =
+            opts.options
+                .determine_increment(&commits, &tag, &self.config.increments)?
+        else {
+            println!(
+                "No release needed: none of the commits since {} require one",
+                tag.version()
+            );
+            return Ok(());
+        };
+        let new_tag = tag.increment_version(increment)?;
+        let build = match &self.config.build_metadata {
+            Some(config) => Some((config, build_metadata(&repo, &config.parts)?)),
+            None => None,
+        };
+        // the tag with build metadata, if it is enabled for the given target
+        let stamped = |target: fn(&BuildMetadataConfig) -> bool| match &build {
+            Some((config, build)) if target(config) => new_tag.with_build_metadata(build.clone()),
+            _ => new_tag.clone(),
+        };
         let version_description = if opts.comment {
             if let Some(description) = prompt_for_description()? {
                 Some(description)
@@ -39,66 +100,257 @@ impl App {
         } else {
             None
         };
-        let new_version_string = new_tag.version();
+        let tag_name = stamped(|config| config.tag).version();
         let changelog_entry = generate_changelog_entry(
             &repo,
             &commits,
-            &new_version_string,
+            &stamped(|config| config.changelog).version(),
+            Some(&tag_name),
             version_description,
             self.config.authors.as_ref(),
+            &self.config.changelog,
         )?;
 
         let replacement = VersionReplacement {
-            old_version: tag.version.to_string(),
-            new_version: new_tag.version.to_string(),
+            old_version: tag.file_version(self.config.build_metadata.as_ref()),
+            new_version: stamped(|config| config.files).bare_version(),
         };
-        let mut file_changes = determine_changes(&self.config, &replacement)?;
-        file_changes.push(apply_changelog(&changelog_entry)?);
+        let mut file_changes = determine_changes(&self.config, &replacement, opts.dry_run)?;
+        file_changes.push(apply_changelog(
+            PathBuf::from("CHANGELOG.md"),
+            &changelog_entry,
+        )?);
         if let Some(changes) = apply_changes(file_changes, opts.dry_run)? {
             let new_tree = prepare_commit(&repo, &changes)?;
             let object_id = repo.write_object(&new_tree)?;
             let commit = repo.commit(
                 "HEAD",
-                format!("chore(version): {}", new_tag.version),
+                format!("chore(version): {}", new_tag.bare_version()),
                 object_id,
                 vec![repo.head_id()?],
             )?;
-            repo.tag_reference(new_version_string, commit, PreviousValue::MustNotExist)?;
+            create_tag(
+                &repo,
+                &tag_name,
+                commit.detach(),
+                self.config
+                    .annotated_tags
+                    .then_some(changelog_entry.as_str()),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Bumps each package of the cargo workspace that changed since its last release, creating a
+    /// tag for each of them in a single commit.
+    fn bump_packages(&self, repo: &gix::Repository, opts: &Bump) -> Result<()> {
+        let template = self
+            .config
+            .tag_template
+            .as_deref()
+            .unwrap_or(PACKAGE_TAG_TEMPLATE);
+        if !template.contains("{package}") {
+            return Err(Error::PackageTagTemplate(template.to_string()));
+        }
+        let build = match &self.config.build_metadata {
+            Some(config) => Some((config, build_metadata(repo, &config.parts)?)),
+            None => None,
+        };
+
+        let mut releases = Vec::new();
+        for package in cargo::workspace_packages()? {
+            let template = TagTemplate::parse(template, Some(&package.name))?;
+            if let Some((tag, new_tag, commits)) =
+                self.package_release(repo, &opts.options, &package, template)?
+            {
+                println!(
+                    "{}: {} -> {}",
+                    style(&package.name).bold(),
+                    tag.bare_version(),
+                    new_tag.bare_version()
+                );
+                releases.push((package, tag, new_tag, commits));
+            }
+        }
+        if releases.is_empty() {
+            println!("No release needed: no packages have changes that require one");
+            return Ok(());
+        }
+
+        // the tag with build metadata, if it is enabled for the given target
+        let stamped = |tag: &Tag, target: fn(&BuildMetadataConfig) -> bool| match &build {
+            Some((config, build)) if target(config) => tag.with_build_metadata(build.clone()),
+            _ => tag.clone(),
+        };
+        let version_description = if opts.comment {
+            if let Some(description) = prompt_for_description()? {
+                Some(description)
+            } else {
+                println!("Aborting bump due to empty description");
+                return Ok(());
+            }
+        } else {
+            None
+        };
+
+        let mut file_changes = Vec::new();
+        let mut bumps = Vec::new();
+        let mut changelog_entries = Vec::new();
+        for (package, tag, new_tag, commits) in &releases {
+            let changelog_entry = generate_changelog_entry(
+                repo,
+                commits,
+                &stamped(new_tag, |config| config.changelog).version(),
+                Some(&stamped(new_tag, |config| config.tag).version()),
+                version_description.clone(),
+                self.config.authors.as_ref(),
+                &self.config.changelog,
+            )?;
+            file_changes.push(apply_changelog(
+                package.directory.join("CHANGELOG.md"),
+                &changelog_entry,
+            )?);
+            changelog_entries.push(changelog_entry);
+            bumps.push(cargo::PackageBump {
+                name: package.name.clone(),
+                versions: VersionReplacement {
+                    old_version: tag.file_version(self.config.build_metadata.as_ref()),
+                    new_version: stamped(new_tag, |config| config.files).bare_version(),
+                },
+            });
+        }
+        if let Some(files) = cargo::Replacer::independent(bumps).determine_replacements()? {
+            file_changes.extend(files);
+        }
+
+        if let Some(changes) = apply_changes(file_changes, opts.dry_run)? {
+            let new_tree = prepare_commit(repo, &changes)?;
+            let object_id = repo.write_object(&new_tree)?;
+            let tag_names = releases
+                .iter()
+                .map(|(_, _, new_tag, _)| stamped(new_tag, |config| config.tag).version())
+                .collect::<Vec<_>>();
+            let commit = repo.commit(
+                "HEAD",
+                format!("chore(version): {}", tag_names.join(", ")),
+                object_id,
+                vec![repo.head_id()?],
+            )?;
+            for (tag_name, changelog_entry) in tag_names.into_iter().zip(&changelog_entries) {
+                create_tag(
+                    repo,
+                    &tag_name,
+                    commit.detach(),
+                    self.config
+                        .annotated_tags
+                        .then_some(changelog_entry.as_str()),
+                )?;
+            }
         }
 
         Ok(())
     }
 
+    /// Determines the next version of a package from the commits that changed its directory since
+    /// its latest tag, returning `None` if it hasn't changed or its changes don't require a
+    /// release. Commits hidden from the changelog are left out.
+    /// Without a tag, the package's current version is its latest release.
+    fn package_release(
+        &self,
+        repo: &gix::Repository,
+        options: &BumpOptions,
+        package: &cargo::WorkspacePackage,
+        template: TagTemplate,
+    ) -> Result<Option<(Tag, Tag, Vec<Commit>)>> {
+        let scheme = self.scheme();
+        let selection = self.config.tag_selection;
+        let tag = match get_latest_tag(repo, &scheme, Some(&template), selection)? {
+            Some(tag) => tag,
+            None => Tag {
+                version: package.version.clone(),
+                commit_id: root_commit(repo)?,
+                prefix_v: false,
+                scheme,
+                template: Some(template),
+            },
+        };
+        let paths = std::slice::from_ref(&package.directory);
+        let mut commits = get_commits_since_tag(repo, &tag, paths, &self.config.commits)?;
+        // the `chore(version)` commits of other packages' releases update this package's
+        // requirements on them, which doesn't require a release of its own
+        commits.retain(|commit| !self.config.changelog.is_hidden(&commit.conventional_commit));
+        if commits.is_empty() {
+            return Ok(None);
+        }
+
+        let Some(increment) =
+            options.determine_increment(&commits, &tag, &self.config.increments)?
+        else {
+            return Ok(None);
+        };
+        let new_tag = tag.increment_version(increment)?;
+        Ok(Some((tag, new_tag, commits)))
+    }
+
     pub fn changelog(&self, opts: &Changelog) -> Result<()> {
         let repo = gix::discover(".")?;
+        let scheme = self.scheme();
+        let template = self.config.tag_template()?;
+        let paths = self.paths(&opts.paths);
         if let Some(version) = &opts.at {
-            let mut tags = Tag::get_version_tags(&repo)?;
+            let version = scheme
+                .parse(version)
+                .ok_or_else(|| Error::InvalidVersion(version.clone()))?;
+            let mut tags = Tag::get_version_tags(
+                &repo,
+                &scheme,
+                template.as_ref(),
+                self.config.tag_selection,
+            )?;
             tags.sort();
             tags.reverse();
             let version_range = tags
                 .windows(2)
                 .find(|tags| {
                     let [first, _] = tags else { unreachable!() };
-                    first.version.eq(version)
+                    first.version.eq(&version)
                 })
                 .ok_or_else(|| Error::VersionNotFound(version.clone()))?;
-            let commits = get_commits_between_tags(&repo, &version_range[1], &version_range[0])?;
+            let commits = get_commits_between_tags(
+                &repo,
+                &version_range[1],
+                &version_range[0],
+                paths,
+                &self.config.commits,
+            )?;
             let changelog_entry = generate_changelog_entry(
                 &repo,
                 &commits,
-                &version.to_string(),
+                &version_range[0].bare_version(),
+                Some(&version_range[0].version()),
                 None,
                 self.config.authors.as_ref(),
+                &self.config.changelog,
             )?;
             println!("{changelog_entry}");
         } else {
-            let (_, commits) = changelog_commits(&repo)?;
+            let (_, commits) = changelog_commits(
+                &repo,
+                &scheme,
+                template.as_ref(),
+                paths,
+                &self.config.commits,
+                self.config.tag_selection,
+            )?;
             let changelog_entry = generate_changelog_entry(
                 &repo,
                 &commits,
                 "unreleased",
                 None,
+                None,
                 self.config.authors.as_ref(),
+                &self.config.changelog,
             )?;
             let path = std::path::PathBuf::from("CHANGELOG.md");
             if opts.no_decorations {
@@ -123,7 +375,7 @@ impl App {
             old_version: opts.old_version.clone(),
             new_version: opts.new_version.clone(),
         };
-        let file_changes = determine_changes(&self.config, &replacement)?;
+        let file_changes = determine_changes(&self.config, &replacement, opts.dry_run)?;
         apply_changes(file_changes, opts.dry_run)?;
 
         Ok(())
@@ -155,30 +407,23 @@ fn apply_changes(changes: Vec<file::Replacer>, dry_run: bool) -> Result<Option<V
 }
 
 /// Determine the changes to make to the repository to update the version.
+/// During a dry run, the files matched by each `by_file` pattern are reported.
 fn determine_changes(
     config: &Config,
     replacement: &VersionReplacement,
+    dry_run: bool,
 ) -> Result<Vec<file::Replacer>> {
     let mut files_to_replace = Vec::new();
 
     let by_file = &config.by_file;
     if let Some(by_file) = by_file {
-        for (path, config) in by_file {
-            let mut replacers = match &config.search_value {
-                Some(value) => search::Replacer::new(
-                    path.clone(),
-                    &replacement.old_version,
-                    value,
-                    &replacement.new_version,
-                )?
-                .determine_replacements()?,
-                None => simple::Replacer::new(
-                    path.clone(),
-                    &replacement.old_version,
-                    &replacement.new_version,
-                )?
-                .determine_replacements()?,
-            };
+        for (path, rules) in expand_by_file(by_file, dry_run)? {
+            let rules = rules
+                .into_iter()
+                .map(|rule| file_rule(&path, rule, replacement))
+                .collect::<Result<_>>()?;
+            let replacer = rules::Replacer::new(path, rules);
+            let mut replacers = replacer.determine_replacements()?;
 
             // append new replacers to the list
             if let Some(replacers) = &mut replacers {
@@ -196,9 +441,114 @@ fn determine_changes(
         }
     }
 
+    if let Some(npm) = &config.npm {
+        let replacer = npm::Replacer::new(replacement.clone(), npm.clone());
+        let mut files = replacer.determine_replacements()?;
+        if let Some(files) = &mut files {
+            files_to_replace.append(files);
+        }
+    }
+
+    if let Some(python) = &config.python {
+        let replacer = python::Replacer::new(replacement.clone(), python.clone());
+        let mut files = replacer.determine_replacements()?;
+        if let Some(files) = &mut files {
+            files_to_replace.append(files);
+        }
+    }
+
+    if let Some(nix) = &config.nix {
+        let replacer = nix::Replacer::new(replacement.clone(), nix.clone());
+        let mut files = replacer.determine_replacements()?;
+        if let Some(files) = &mut files {
+            files_to_replace.append(files);
+        }
+    }
+
     Ok(files_to_replace)
 }
 
+/// Creates the replacer for a single `by_file` rule.
+fn file_rule(
+    path: &Path,
+    rule: &FileTableData,
+    replacement: &VersionReplacement,
+) -> Result<rules::Rule> {
+    let replacer: Box<dyn ContentReplacer> = match rule.mode()? {
+        RuleMode::Selector(value_selector) => Box::new(selector::Replacer::new(
+            path.to_path_buf(),
+            value_selector.clone(),
+            replacement.clone(),
+        )),
+        RuleMode::Regex(regex) => Box::new(capture::Replacer::new(
+            path.to_path_buf(),
+            regex,
+            replacement.clone(),
+        )?),
+        RuleMode::YamlPaths(value_paths) => Box::new(yaml::Replacer::new(
+            path.to_path_buf(),
+            &replacement.old_version,
+            value_paths,
+            &replacement.new_version,
+        )?),
+        RuleMode::Search(value) => Box::new(search::Replacer::new(
+            path.to_path_buf(),
+            &replacement.old_version,
+            value,
+            &replacement.new_version,
+        )?),
+        RuleMode::Simple => Box::new(simple::Replacer::new(
+            path.to_path_buf(),
+            &replacement.old_version,
+            &replacement.new_version,
+        )?),
+    };
+
+    Ok(rules::Rule {
+        description: rule.to_string(),
+        expect: rule.expectation(),
+        replacer,
+    })
+}
+
+/// Expand the glob patterns used as `by_file` keys into the files they match, grouping the rules
+/// that apply to each file.
+/// Patterns are matched relative to the repository root, skipping files ignored by git.
+/// Keys without glob syntax are used as they are.
+fn expand_by_file(
+    by_file: &HashMap<PathBuf, FileRules>,
+    dry_run: bool,
+) -> Result<BTreeMap<PathBuf, Vec<&FileTableData>>> {
+    let mut keys = by_file.keys().collect::<Vec<_>>();
+    keys.sort();
+
+    let mut files: BTreeMap<PathBuf, Vec<&FileTableData>> = BTreeMap::new();
+    for key in keys {
+        let rules = by_file[key].rules();
+        let pattern = key.to_string_lossy();
+        if !glob::is_pattern(&pattern) {
+            files.entry(key.clone()).or_default().extend(rules);
+            continue;
+        }
+
+        let paths = glob::expand(&[&pattern])?;
+        if paths.is_empty() {
+            return Err(Error::NoGlobMatches(pattern.into_owned()));
+        }
+        if dry_run {
+            println!("{} matched:", style(&pattern).bold());
+            for path in &paths {
+                println!("  {}", path.display());
+            }
+        }
+        for path in paths {
+            files.entry(path).or_default().extend(rules);
+        }
+    }
+
+    Ok(files)
+}
+
 /// Stitch together the existing changelog with the new one.
 /// This is done using `- - -` as a marker character.
 /// The new changelog is composed of the changelog header (everything from the start to the first
@@ -222,8 +572,7 @@ fn create_changelog(path: &std::path::Path, contents: &str) -> Result<String> {
     }
 }
 
-fn apply_changelog(entry: &str) -> Result<file::Replacer> {
-    let path = std::path::PathBuf::from("CHANGELOG.md");
+fn apply_changelog(path: PathBuf, entry: &str) -> Result<file::Replacer> {
     let new_changelog = create_changelog(&path, entry)?;
 
     let temp_file = tempfile::NamedTempFile::new_in(".")?;
@@ -275,7 +624,7 @@ fn rewrite_tree(
                 let file_name = entry.filename.clone().to_string();
                 let file_path = tree_path.join(file_name);
                 if let Some(new_path) = changes.iter().find(|p| **p == file_path) {
-                    println!("replacing {new_path:?}");
+                    println!("replacing {}", new_path.display());
                     let new_id = repo.write_blob_stream(std::fs::File::open(new_path)?)?;
 
                     new_entries.push(gix::worktree::object::tree::Entry {
@@ -291,6 +640,29 @@ fn rewrite_tree(
         }
     }
 
+    // files that don't exist yet in this directory, such as a new changelog
+    for new_path in changes {
+        let (Some(parent), Some(file_name)) = (new_path.parent(), new_path.file_name()) else {
+            continue;
+        };
+        let file_name = file_name.to_string_lossy();
+        let exists = tree
+            .entries
+            .iter()
+            .any(|entry| entry.filename == *file_name);
+        if parent != tree_path.as_path() || exists {
+            continue;
+        }
+        println!("adding {}", new_path.display());
+        let new_id = repo.write_blob_stream(std::fs::File::open(new_path)?)?;
+        new_entries.push(gix::worktree::object::tree::Entry {
+            filename: file_name.as_ref().into(),
+            mode: gix::object::tree::EntryKind::Blob.into(),
+            oid: new_id.into(),
+        });
+    }
+    new_entries.sort();
+
     Ok(gix::worktree::object::Tree {
         entries: new_entries,
     })
@@ -383,21 +755,35 @@ fn prompt_for_description() -> Result<Option<String>> {
 
 /// Retrieve all the commits that should be included in a new changelog entry.
 /// This will start at the current head commit and walk back to the latest tag.
-/// The latest tag is determined by the highest semver tag in the repository.
+/// The latest tag is determined by the highest version tag of the scheme in the repository.
 /// If no tags are found, the root commit will be used as the starting point and a version of `0.0.0` will be used.
-fn changelog_commits(repo: &gix::Repository) -> Result<(Tag, Vec<Commit>)> {
-    let tag = if let Some(tag) = get_latest_tag(repo)? {
+fn changelog_commits(
+    repo: &gix::Repository,
+    scheme: &VersionScheme,
+    template: Option<&TagTemplate>,
+    paths: &[PathBuf],
+    policy: &CommitPolicy,
+    selection: TagSelection,
+) -> Result<(Tag, Vec<Commit>)> {
+    let tag = if let Some(tag) = get_latest_tag(repo, scheme, template, selection)? {
         tag
     } else {
-        let head = repo.head_commit()?;
-        let ancestors = head.ancestors();
-        let root_commit = ancestors.all()?.last();
         Tag {
             version: semver::Version::new(0, 0, 0),
-            commit_id: root_commit.unwrap().unwrap().id().into(),
+            commit_id: root_commit(repo)?,
             prefix_v: false,
+            scheme: scheme.clone(),
+            template: template.cloned(),
         }
     };
-    let commits = get_commits_since_tag(repo, &tag)?;
+    let commits = get_commits_since_tag(repo, &tag, paths, policy)?;
     Ok((tag, commits))
 }
+
+/// Finds the first commit in the history of HEAD.
+fn root_commit(repo: &gix::Repository) -> Result<gix::ObjectId> {
+    let head = repo.head_commit()?;
+    let ancestors = head.ancestors();
+    let root_commit = ancestors.all()?.last();
+    Ok(root_commit.unwrap().unwrap().id().into())
+}
@@ -1,5 +1,6 @@
-use bomper::error::Result;
-use bomper::versioning::{determine_increment, Commit, VersionIncrement};
+use bomper::config::IncrementRules;
+use bomper::error::{Error, Result};
+use bomper::versioning::{determine_increment, Commit, Tag, VersionIncrement};
 use clap::Parser;
 use std::path::PathBuf;
 
@@ -46,6 +47,12 @@ pub(crate) struct Bump {
     #[clap(flatten)]
     pub options: BumpOptions,
 
+    /// only count commits that changed files under the given path, relative to the repository
+    /// root. may be given multiple times, replacing the configured paths.
+    /// packages versioned independently use their own directory instead.
+    #[arg(long = "path", value_name = "PATH")]
+    pub paths: Vec<PathBuf>,
+
     /// Whether to prompt for a hand-written summary message
     #[arg(short, long)]
     pub comment: bool,
@@ -58,7 +65,7 @@ pub(crate) struct Bump {
 pub(crate) struct Changelog {
     /// the version to start generate the changelog for.
     #[arg(long)]
-    pub at: Option<semver::Version>,
+    pub at: Option<String>,
 
     /// output the changelog in plain style, with no decorations.
     #[arg(short, long)]
@@ -67,10 +74,19 @@ pub(crate) struct Changelog {
     /// only valid when `no_decorations` is set.
     #[arg(short, long, requires = "no_decorations")]
     pub only_current_version: bool,
+
+    /// only include commits that changed files under the given path, relative to the repository
+    /// root. may be given multiple times, replacing the configured paths.
+    #[arg(long = "path", value_name = "PATH")]
+    pub paths: Vec<PathBuf>,
 }
 
 #[derive(clap::Args, Debug)]
-#[command(group = clap::ArgGroup::new("bump-type").required(true))]
+#[command(group = clap::ArgGroup::new("bump-type"))]
+#[command(group = clap::ArgGroup::new("bump")
+    .required(true)
+    .multiple(true)
+    .args(["version", "automatic", "major", "minor", "patch", "release", "pre"]))]
 #[allow(clippy::struct_excessive_bools)]
 pub(crate) struct BumpOptions {
     #[arg(short, long, group = "bump-type")]
@@ -83,23 +99,91 @@ pub(crate) struct BumpOptions {
     pub minor: bool,
     #[arg(short, long, group = "bump-type")]
     pub patch: bool,
+    /// promote the current pre-release to its final version
+    #[arg(long, group = "bump-type")]
+    pub release: bool,
+    /// start or advance a pre-release with the given identifier, such as `rc`.
+    /// without another bump type, the increment is determined from the commits.
+    #[arg(
+        long,
+        value_name = "IDENTIFIER",
+        conflicts_with_all = ["version", "release"]
+    )]
+    pub pre: Option<String>,
 }
 
 impl BumpOptions {
+    /// Determines the increment to apply to the current version.
+    /// Returns `None` when the increment is determined from the commits, and none of them require
+    /// a release.
     pub(crate) fn determine_increment<'a, I: IntoIterator<Item = &'a Commit>>(
         &self,
         commits: I,
-        current_version: &semver::Version,
-    ) -> Result<VersionIncrement> {
+        current: &Tag,
+        rules: &IncrementRules,
+    ) -> Result<Option<VersionIncrement>> {
+        let Some(increment) = self.determine_base_increment(commits, current, rules)? else {
+            return Ok(None);
+        };
+        match &self.pre {
+            Some(identifier) => {
+                // the identifier is followed by a number, so it must be a single part that forms a
+                // valid pre-release with it, which rules out numbers with leading zeros
+                let valid = !identifier.contains('.')
+                    && semver::Prerelease::new(&format!("{identifier}.1")).is_ok();
+                if !valid {
+                    return Err(Error::InvalidPrereleaseIdentifier(identifier.clone()));
+                }
+                Ok(Some(VersionIncrement::Prerelease {
+                    increment: Box::new(increment),
+                    identifier: identifier.clone(),
+                }))
+            }
+            None => Ok(Some(increment)),
+        }
+    }
+
+    fn determine_base_increment<'a, I: IntoIterator<Item = &'a Commit>>(
+        &self,
+        commits: I,
+        current: &Tag,
+        rules: &IncrementRules,
+    ) -> Result<Option<VersionIncrement>> {
+        let current_version = &current.version;
         match &self.version {
-            Some(version) => Ok(VersionIncrement::Manual(semver::Version::parse(version)?)),
+            Some(version) => {
+                let version = current
+                    .scheme
+                    .parse(version)
+                    .ok_or_else(|| Error::InvalidVersion(version.clone()))?;
+                Ok(Some(VersionIncrement::Manual(version)))
+            }
             None if self.automatic => {
                 let conventional_commits = commits.into_iter().map(std::convert::AsRef::as_ref);
-                Ok(determine_increment(conventional_commits, current_version))
+                Ok(determine_increment(
+                    conventional_commits,
+                    current_version,
+                    rules,
+                ))
+            }
+            None if self.major => Ok(Some(VersionIncrement::Major)),
+            None if self.minor => Ok(Some(VersionIncrement::Minor)),
+            None if self.patch => Ok(Some(VersionIncrement::Patch)),
+            None if self.release => {
+                if current_version.pre.is_empty() {
+                    return Err(Error::NotPrerelease(current_version.clone()));
+                }
+                Ok(Some(VersionIncrement::Release))
+            }
+            // `--pre` on its own determines the increment from the commits
+            None if self.pre.is_some() => {
+                let conventional_commits = commits.into_iter().map(std::convert::AsRef::as_ref);
+                Ok(determine_increment(
+                    conventional_commits,
+                    current_version,
+                    rules,
+                ))
             }
-            None if self.major => Ok(VersionIncrement::Major),
-            None if self.minor => Ok(VersionIncrement::Minor),
-            None if self.patch => Ok(VersionIncrement::Patch),
             _ => unreachable!(),
         }
     }
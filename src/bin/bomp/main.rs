@@ -1,3 +1,4 @@
+#![allow(clippy::result_large_err)]
 use bomper::config::Config;
 use clap::Parser;
 use std::path::PathBuf;
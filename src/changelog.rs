@@ -1,43 +1,84 @@
-use crate::{error::Result, versioning};
+use crate::{
+    config::ChangelogConfig,
+    error::{Error, Result},
+    versioning,
+};
 use conventional_commit_parser::commit::CommitType;
-use std::collections::HashMap;
+use regex::Regex;
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
 
 const TEMPLATE: &str = include_str!("templates/changelog_entry.md");
+const COMMIT_TEMPLATE: &str = include_str!("templates/changelog_commit.md");
 
+/// The context of changelog templates, available as `entry`.
 #[derive(Debug, serde::Serialize)]
 pub struct Entry<'a> {
+    /// the version being released, or `unreleased`
     pub version: &'a str,
-    pub commits: HashMap<String, Vec<Commit>>,
+    /// the date of the release, formatted as `YYYY-MM-DD`
+    pub date: String,
+    /// the breaking commits, including those of hidden types, which are also listed in their
+    /// sections
+    pub breaking_changes: Vec<Commit>,
+    pub sections: Vec<Section>,
     pub description: Option<String>,
+    pub links: EntryLinks,
 }
 
-#[derive(Clone, Debug)]
+/// Links to the pages of the release on the repository's host, if the repository has a remote.
+#[derive(Debug, serde::Serialize)]
+pub struct EntryLinks {
+    /// the page of the release's tag, unless the entry is unreleased
+    pub release: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct Section {
+    pub title: String,
+    pub commits: Vec<Commit>,
+}
+
+/// A commit listed in a changelog entry, available as `commit` in the commit template.
+#[derive(Clone, Debug, serde::Serialize)]
 pub struct Commit {
+    /// the type of the commit as written in its message, such as `feat`
+    #[serde(rename = "type")]
+    pub commit_type: String,
     pub scope: Option<String>,
     pub summary: String,
+    pub body: Option<String>,
+    pub footers: Vec<Footer>,
+    pub breaking: bool,
+    /// the text of the commit's `BREAKING CHANGE` footers, if it has any
+    pub breaking_description: Option<String>,
     pub hash: String,
+    /// the first 7 characters of the hash
+    pub short_hash: String,
+    /// the name of the commit's author
     pub author: String,
+    /// the username of the author on the repository's host, from the `authors` configuration
+    pub username: Option<String>,
+    pub links: CommitLinks,
 }
 
-impl std::fmt::Display for Commit {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self.scope {
-            Some(ref scope) => write!(
-                f,
-                "**({0})** {1} - ({2}) - {3}",
-                scope, self.summary, self.hash, self.author
-            ),
-            None => write!(f, "{0} - ({1}) - {2}", self.summary, self.hash, self.author),
-        }
-    }
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct Footer {
+    pub token: String,
+    pub content: String,
 }
 
-impl serde::Serialize for Commit {
-    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        serializer.collect_str(self)
-    }
+/// Links to the pages of a commit on the repository's host, if the repository has a remote.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct CommitLinks {
+    pub commit: Option<String>,
+    /// the profile of the author, if they have a username
+    pub author: Option<String>,
 }
 
+/// The name used for a commit type in the changelog when it has no configured section.
 #[must_use]
 pub fn display_commit_type(commit_type: &CommitType) -> String {
     match commit_type {
@@ -52,13 +93,19 @@ pub fn display_commit_type(commit_type: &CommitType) -> String {
         CommitType::Revert => "reverts".to_string(),
         CommitType::Ci => "continuous integration".to_string(),
         CommitType::Performances => "performance".to_string(),
-        CommitType::Custom(custom) => custom.to_string(),
+        CommitType::Custom(custom) => custom.clone(),
     }
 }
 
+/// Renders a changelog entry, grouping the commits into the sections configured in `config`.
+/// The entry is rendered with the configured templates if there are any, otherwise with the
+/// built-in ones. The heading links to the release of `tag`, the name of the release's tag, when
+/// it is given.
+///
 /// # Errors
 ///
-/// - if the template is invalid, however this is unlikely to happen since the template is hardcoded
+/// - if a configured template can't be read
+/// - if a template is invalid or fails to render
 pub fn generate_changelog_entry<
     'a,
     I: IntoIterator<Item = &'a versioning::Commit>,
@@ -67,46 +114,53 @@ pub fn generate_changelog_entry<
     repo: &gix::Repository,
     commits: I,
     version: &str,
+    tag: Option<&str>,
     description: Option<String>,
     authors: Option<&HashMap<String, String, S>>,
+    config: &ChangelogConfig,
 ) -> Result<String> {
+    let template = read_template(config.template.as_deref(), TEMPLATE)?;
+    let commit_template = read_template(config.commit_template.as_deref(), COMMIT_TEMPLATE)?;
     let mut env = minijinja::Environment::new();
-    env.add_template("changelog_entry", TEMPLATE)?;
+    env.add_template("changelog_entry", &template)?;
+    env.add_template("changelog_commit", &commit_template)?;
 
     let url = gix_repo_url(repo)?;
-    let version = match &url {
-        Some((host, path)) => &format!("[{version}](https://{host}/{path}/releases/tag/{version})"),
-        None => version,
-    };
-    let version = &format!("{} - {}", version, chrono::Local::now().format("%Y-%m-%d"));
-
-    let typed_commits: HashMap<String, Vec<Commit>> =
-        commits.into_iter().fold(HashMap::new(), |mut acc, commit| {
-            let key = display_commit_type(&commit.conventional_commit.commit_type);
-            let entry = acc.entry(key).or_default();
-            let author = author_name(commit.signature.name.to_string(), authors, url.as_ref());
-            let commit_id = commit.commit_id.to_string();
-            let hash = match &url {
-                Some((host, path)) => format!(
-                    "[{}](https://{host}/{path}/commit/{commit_id})",
-                    &commit_id[..7]
-                ),
-                None => commit_id,
-            };
-
-            let commit = Commit {
-                scope: commit.conventional_commit.scope.clone(),
-                summary: commit.conventional_commit.summary.clone(),
-                hash,
-                author,
-            };
-            entry.push(commit);
-            acc
-        });
+    let references = References::new(config, url.as_ref())?;
+
+    // sections are ordered by their position in the configuration, then by title
+    let mut sections: BTreeMap<(usize, String), Vec<Commit>> = BTreeMap::new();
+    let mut breaking_changes = Vec::new();
+    for commit in commits {
+        let changelog_commit = changelog_commit(commit, authors, url.as_ref(), &references);
+        if changelog_commit.breaking {
+            breaking_changes.push(changelog_commit.clone());
+        }
+
+        let Some(title) = config.section_of(&commit.conventional_commit) else {
+            continue;
+        };
+        let position = config.position(&title).unwrap_or(usize::MAX);
+        sections
+            .entry((position, title))
+            .or_default()
+            .push(changelog_commit);
+    }
     let entry = Entry {
         version,
-        commits: typed_commits,
+        date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+        breaking_changes,
+        sections: sections
+            .into_iter()
+            .map(|((_, title), commits)| Section { title, commits })
+            .collect(),
         description,
+        links: EntryLinks {
+            release: url
+                .as_ref()
+                .zip(tag)
+                .map(|((host, path), tag)| format!("https://{host}/{path}/releases/tag/{tag}")),
+        },
     };
 
     let template = env.get_template("changelog_entry")?;
@@ -117,6 +171,126 @@ pub fn generate_changelog_entry<
         .map_err(Into::into)
 }
 
+fn read_template(path: Option<&Path>, default: &str) -> Result<String> {
+    match path {
+        Some(path) => std::fs::read_to_string(path).map_err(|source| Error::ChangelogTemplate {
+            path: path.to_path_buf(),
+            source,
+        }),
+        None => Ok(default.to_string()),
+    }
+}
+
+fn changelog_commit<S: ::std::hash::BuildHasher>(
+    commit: &versioning::Commit,
+    authors: Option<&HashMap<String, String, S>>,
+    url: Option<&(String, String)>,
+    references: &References,
+) -> Commit {
+    let conventional_commit = &commit.conventional_commit;
+    let hash = commit.commit_id.to_string();
+    let author = commit.signature.name.to_string();
+    let username = authors.and_then(|authors| authors.get(&author).cloned());
+    let breaking_footers = conventional_commit
+        .footers
+        .iter()
+        .filter(|footer| footer.is_breaking_change())
+        .map(|footer| references.link(footer.content.trim()))
+        .collect::<Vec<_>>();
+
+    Commit {
+        commit_type: conventional_commit.commit_type.as_ref().to_string(),
+        scope: conventional_commit.scope.clone(),
+        summary: references.link(&conventional_commit.summary),
+        body: conventional_commit.body.clone(),
+        footers: conventional_commit
+            .footers
+            .iter()
+            .map(|footer| Footer {
+                token: footer.token.clone(),
+                content: references.link(&footer.content),
+            })
+            .collect(),
+        breaking: conventional_commit.is_breaking_change,
+        breaking_description: (!breaking_footers.is_empty()).then(|| breaking_footers.join("\n")),
+        short_hash: hash[..7].to_string(),
+        links: CommitLinks {
+            commit: url.map(|(host, path)| format!("https://{host}/{path}/commit/{hash}")),
+            author: url
+                .zip(username.as_ref())
+                .map(|((host, _), username)| format!("https://{host}/{username}")),
+        },
+        hash,
+        author,
+        username,
+    }
+}
+
+/// Links references to issues, pull requests, and external trackers in commit messages.
+struct References {
+    patterns: Vec<(Regex, String)>,
+}
+
+impl References {
+    fn new(config: &ChangelogConfig, url: Option<&(String, String)>) -> Result<Self> {
+        let mut patterns = Vec::new();
+        if let (true, Some((host, path))) = (config.forge_references, url) {
+            let base = format!("https://{host}/{path}");
+            if host.contains("gitlab") {
+                patterns.push((Regex::new(r"\B#(\d+)\b")?, format!("{base}/-/issues/$1")));
+                patterns.push((
+                    Regex::new(r"\B!(\d+)\b")?,
+                    format!("{base}/-/merge_requests/$1"),
+                ));
+            } else {
+                // GitHub and Gitea redirect issue links to pull requests
+                patterns.push((Regex::new(r"\B#(\d+)\b")?, format!("{base}/issues/$1")));
+            }
+        }
+        for reference in &config.references {
+            patterns.push((Regex::new(&reference.pattern)?, reference.url.clone()));
+        }
+
+        Ok(Self { patterns })
+    }
+
+    /// Replaces each reference in `text` with a markdown link.
+    /// All patterns are matched against the original text, so a link is never matched again.
+    /// Where matches overlap, the earliest one is linked.
+    fn link(&self, text: &str) -> String {
+        let mut matches = self
+            .patterns
+            .iter()
+            .flat_map(|(regex, url)| {
+                regex.captures_iter(text).map(move |captures| {
+                    let mut link = String::new();
+                    captures.expand(url, &mut link);
+                    let reference = captures.get(0).expect("a match has a whole capture");
+                    (reference.range(), link)
+                })
+            })
+            .collect::<Vec<_>>();
+        matches.sort_by_key(|(range, _)| range.start);
+
+        let mut linked = String::with_capacity(text.len());
+        let mut end = 0;
+        for (range, link) in matches {
+            if range.start < end || range.is_empty() {
+                continue;
+            }
+            linked.push_str(&text[end..range.start]);
+            linked.push('[');
+            linked.push_str(&text[range.start..range.end]);
+            linked.push_str("](");
+            linked.push_str(&link);
+            linked.push(')');
+            end = range.end;
+        }
+        linked.push_str(&text[end..]);
+        linked
+    }
+}
+
 fn gix_repo_url(repo: &gix::Repository) -> Result<Option<(String, String)>> {
     let remote = match repo.find_default_remote(gix::remote::Direction::Push) {
         Some(remote) => remote?,
@@ -130,7 +304,9 @@ fn gix_repo_url(repo: &gix::Repository) -> Result<Option<(String, String)>> {
             match (host, path) {
                 (Some(host), Some(path)) => Ok(Some((
                     host.to_string(),
-                    remove_suffix(&path.to_string(), ".git").to_string(),
+                    remove_suffix(&path.to_string(), ".git")
+                        .trim_start_matches('/')
+                        .to_string(),
                 ))),
                 _ => Ok(None),
             }
@@ -146,24 +322,3 @@ fn remove_suffix<'a>(input: &'a str, suffix: &str) -> &'a str {
         input
     }
 }
-
-fn author_name<S: ::std::hash::BuildHasher>(
-    commit_author: String,
-    authors: Option<&HashMap<String, String, S>>,
-    url: Option<&(String, String)>,
-) -> String {
-    match url {
-        Some((host, _)) => {
-            if let Some(authors) = authors {
-                authors
-                    .get(&commit_author)
-                    .cloned()
-                    .map(|author| format!("[@{author}](https://{host}/{author})"))
-                    .unwrap_or(commit_author)
-            } else {
-                commit_author
-            }
-        }
-        None => commit_author,
-    }
-}
@@ -1,35 +1,615 @@
-use serde::{Deserialize, Serialize};
+use serde::{
+    de::{self, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
 use std::{
     collections::HashMap,
     path::{Path, PathBuf},
 };
 
-use crate::error::Result;
+use crate::changelog::display_commit_type;
+use crate::error::{Error, Result};
+use crate::versioning::{TagTemplate, VersionScheme};
+use conventional_commit_parser::commit::{CommitType, ConventionalCommit};
+
+/// The rules applied to a `by_file` entry.
+/// A file containing the version in several different contexts can be given a list of rules,
+/// which are all applied to the file in a single pass.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum FileRules {
+    Single(FileTableData),
+    Multiple(Vec<FileTableData>),
+}
+
+impl FileRules {
+    #[must_use]
+    pub fn rules(&self) -> &[FileTableData] {
+        match self {
+            FileRules::Single(rule) => std::slice::from_ref(rule),
+            FileRules::Multiple(rules) => rules,
+        }
+    }
+}
+
+// An untagged enum can't be used here because it would reject a rule written as `()`
+impl<'de> Deserialize<'de> for FileRules {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct RulesVisitor;
+
+        impl<'de> Visitor<'de> for RulesVisitor {
+            type Value = FileRules;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a rule or a list of rules")
+            }
+
+            fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+                Ok(FileRules::Single(FileTableData::default()))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                FileTableData::deserialize(de::value::MapAccessDeserializer::new(map))
+                    .map(FileRules::Single)
+            }
 
+            fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+                Vec::deserialize(de::value::SeqAccessDeserializer::new(seq))
+                    .map(FileRules::Multiple)
+            }
+        }
+
+        deserializer.deserialize_any(RulesVisitor)
+    }
+}
+
+/// A rule for replacing the version in a file.
+/// `search_value`, `yaml_paths`, `selector`, and `regex` select how the version is found, so at
+/// most one of them can be set. Without any of them, every occurrence of the version is replaced.
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FileTableData {
+    /// a regex that must match the two lines preceding an occurrence of the version for it to be
+    /// updated
     pub search_value: Option<String>,
+    /// paths to values in a YAML file that should be updated, such as `appVersion` or
+    /// `spec.template.spec.containers[*].image`
+    pub yaml_paths: Option<Vec<String>>,
+    /// a single structured value to update, which must be set to the old version
+    pub selector: Option<Selector>,
+    /// a regex whose `version` capture group is replaced, for files without a structured format
+    pub regex: Option<RegexReplace>,
+    /// the number of values the rule must replace, see [`FileTableData::expectation`]
+    /// A selector always replaces a single value, so it can only expect exactly one.
+    pub expect: Option<Expect>,
+}
+
+/// How a [`FileTableData`] rule finds the version.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RuleMode<'a> {
+    Simple,
+    Search(&'a str),
+    YamlPaths(&'a [String]),
+    Selector(&'a Selector),
+    Regex(&'a RegexReplace),
+}
+
+impl FileTableData {
+    /// Determines how the rule finds the version.
+    ///
+    /// # Errors
+    /// - If more than one of `search_value`, `yaml_paths`, `selector`, and `regex` is set
+    pub fn mode(&self) -> Result<RuleMode<'_>> {
+        let mut modes = Vec::new();
+        if let Some(search_value) = &self.search_value {
+            modes.push(("search_value", RuleMode::Search(search_value)));
+        }
+        if let Some(yaml_paths) = &self.yaml_paths {
+            modes.push(("yaml_paths", RuleMode::YamlPaths(yaml_paths)));
+        }
+        if let Some(selector) = &self.selector {
+            modes.push(("selector", RuleMode::Selector(selector)));
+        }
+        if let Some(regex) = &self.regex {
+            modes.push(("regex", RuleMode::Regex(regex)));
+        }
+
+        match modes.as_slice() {
+            [] => Ok(RuleMode::Simple),
+            [(_, mode)] => Ok(*mode),
+            modes => Err(Error::ConflictingFileRule(
+                modes
+                    .iter()
+                    .map(|(name, _)| format!("`{name}`"))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            )),
+        }
+    }
+
+    /// Checks that the rule has a single mode and an expectation it can meet.
+    ///
+    /// # Errors
+    /// - If the rule has more than one mode, see [`FileTableData::mode`]
+    /// - If a selector rule expects anything other than exactly one replacement, since a selector
+    ///   always replaces a single value
+    pub fn validate(&self) -> Result<()> {
+        match (self.mode()?, self.expect) {
+            (RuleMode::Selector(selector), Some(expect)) if expect != Expect::Exactly(1) => {
+                Err(Error::SelectorExpectation {
+                    selector: selector.clone(),
+                    expect,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The number of values the rule must replace.
+    /// Unless set explicitly, a selector must replace exactly one value, the other targeted rules
+    /// at least one, and a simple replacement any number.
+    #[must_use]
+    pub fn expectation(&self) -> Expect {
+        match self.expect {
+            Some(expect) => expect,
+            None if self.selector.is_some() => Expect::Exactly(1),
+            None if self.regex.is_some() || self.yaml_paths.is_some() => Expect::AtLeast(1),
+            None if self.search_value.is_some() => Expect::AtLeast(1),
+            None => Expect::Any,
+        }
+    }
+}
+
+impl std::fmt::Display for FileTableData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(selector) = &self.selector {
+            write!(f, "{selector}")
+        } else if let Some(regex) = &self.regex {
+            write!(f, "regex `{}`", regex.pattern)
+        } else if let Some(yaml_paths) = &self.yaml_paths {
+            write!(f, "yaml paths `{}`", yaml_paths.join("`, `"))
+        } else if let Some(search_value) = &self.search_value {
+            write!(f, "search value `{search_value}`")
+        } else {
+            write!(f, "simple replacement")
+        }
+    }
+}
+
+/// The number of values a rule is expected to replace.
+/// A release fails if a rule's replacements don't meet its expectation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Expect {
+    Exactly(usize),
+    AtLeast(usize),
+    Any,
+}
+
+impl Expect {
+    #[must_use]
+    pub fn is_met(self, count: usize) -> bool {
+        match self {
+            Expect::Exactly(expected) => count == expected,
+            Expect::AtLeast(minimum) => count >= minimum,
+            Expect::Any => true,
+        }
+    }
+}
+
+impl std::fmt::Display for Expect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expect::Exactly(expected) => write!(f, "exactly {expected}"),
+            Expect::AtLeast(minimum) => write!(f, "at least {minimum}"),
+            Expect::Any => write!(f, "any number"),
+        }
+    }
+}
+
+/// Replaces the span captured by the `version` group of each match of a regex,
+/// such as `LABEL version="(?P<version>[^"]+)"`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegexReplace {
+    pub pattern: String,
+    /// fail if a captured value is not the old version
+    #[serde(default)]
+    pub verify: bool,
+}
+
+/// Identifies exactly one value in a structured file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Selector {
+    /// a JSON pointer, such as `/version` or `/packages/0/version`
+    Json(String),
+    /// a dotted TOML key, such as `package.version` or `tool.poetry.version`
+    Toml(String),
+    /// a YAML path, such as `appVersion` or `items[0].version`
+    Yaml(String),
+}
+
+impl std::fmt::Display for Selector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Selector::Json(pointer) => write!(f, "json pointer `{pointer}`"),
+            Selector::Toml(key) => write!(f, "toml key `{key}`"),
+            Selector::Yaml(path) => write!(f, "yaml path `{path}`"),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
 pub struct Config {
-    pub by_file: Option<HashMap<PathBuf, FileTableData>>,
+    pub by_file: Option<HashMap<PathBuf, FileRules>>,
     #[serde(default)]
     pub cargo: Option<CargoReplaceMode>,
+    #[serde(default)]
+    pub npm: Option<NpmReplaceMode>,
+    #[serde(default)]
+    pub python: Option<PythonConfig>,
+    #[serde(default)]
+    pub nix: Option<NixConfig>,
+    /// the versioning scheme of the project, defaulting to semver
+    #[serde(default)]
+    pub scheme: Option<VersionScheme>,
+    /// the name of version tags, such as `v{version}` or `{package}@{version}`.
+    /// By default, tags are named after the version, optionally prefixed with `v`.
+    #[serde(default)]
+    pub tag_template: Option<String>,
+    /// the name of the package, used for `{package}` in the tag template
+    #[serde(default)]
+    pub package: Option<String>,
+    /// the directories of the package, relative to the repository root.
+    /// Only commits that changed files within them count towards the version and the changelog.
+    /// By default, every commit counts.
+    #[serde(default)]
+    pub paths: Vec<PathBuf>,
+    /// which version tags are considered when finding the latest version
+    #[serde(default)]
+    pub tag_selection: TagSelection,
+    /// whether to create annotated tags, with the changelog entry as their message, instead of
+    /// lightweight tags
+    #[serde(default)]
+    pub annotated_tags: bool,
+    /// how commits determine the automatic increment
+    #[serde(default)]
+    pub increments: IncrementRules,
+    /// how commits are selected for the changelog and the automatic increment
+    #[serde(default)]
+    pub commits: CommitPolicy,
+    /// how commits are grouped into the sections of changelog entries
+    #[serde(default)]
+    pub changelog: ChangelogConfig,
+    /// build metadata to append to the new version during a bump
+    #[serde(default)]
+    pub build_metadata: Option<BuildMetadataConfig>,
     /// mapping of commit author to GitHub username
     pub authors: Option<HashMap<String, String>>,
 }
 
 impl Config {
+    /// Parses the tag template, if one is configured.
+    ///
+    /// # Errors
+    /// - If the template is invalid, see [`TagTemplate::parse`]
+    pub fn tag_template(&self) -> Result<Option<TagTemplate>> {
+        self.tag_template
+            .as_deref()
+            .map(|template| TagTemplate::parse(template, self.package.as_deref()))
+            .transpose()
+    }
+
     /// # Errors
     /// - If the file cannot be read
     /// - If the file is not valid RON
+    /// - If a file rule is invalid, see [`FileTableData::validate`]
     pub fn from_ron(path: &impl AsRef<Path>) -> Result<Config> {
         let file = std::fs::read_to_string(path)?;
         let value: Config = ron::from_str(&file)?;
+        value.validate()?;
 
         Ok(value)
     }
+
+    /// Checks the parts of the configuration that can't be checked while deserializing it.
+    ///
+    /// # Errors
+    /// - If build metadata is written to files but not to tags, see [`BuildMetadataConfig::files`]
+    /// - If a file rule is invalid, see [`FileTableData::validate`]
+    pub fn validate(&self) -> Result<()> {
+        if self
+            .build_metadata
+            .as_ref()
+            .is_some_and(|build| build.files && !build.tag)
+        {
+            return Err(Error::BuildMetadataFilesWithoutTag);
+        }
+
+        self.by_file
+            .iter()
+            .flat_map(HashMap::values)
+            .flat_map(FileRules::rules)
+            .try_for_each(FileTableData::validate)
+    }
+}
+
+/// How commits determine the automatic version increment.
+/// Breaking changes always require a major increment. Other commits use the first rule that
+/// matches them, falling back to a minor increment for features and `default` for the rest.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IncrementRules {
+    #[serde(default)]
+    pub rules: Vec<IncrementRule>,
+    /// the increment of commits that don't match any rule, other than features
+    #[serde(default = "default_increment")]
+    pub default: Increment,
+    /// whether major increments are downgraded to minor ones while the major version is 0, as is
+    /// common before a 1.0.0 release
+    #[serde(default = "enabled")]
+    pub initial_development: bool,
+}
+
+impl IncrementRules {
+    /// Determines the increment required by a single commit.
+    #[must_use]
+    pub fn increment_of(&self, commit: &ConventionalCommit) -> Increment {
+        if commit.is_breaking_change {
+            return Increment::Major;
+        }
+
+        let rule = self.rules.iter().find(|rule| {
+            CommitType::from(rule.commit_type.as_str()) == commit.commit_type
+                && (rule.scope.is_none() || rule.scope == commit.scope)
+        });
+        match rule {
+            Some(rule) => rule.increment,
+            None if commit.commit_type == CommitType::Feature => Increment::Minor,
+            None => self.default,
+        }
+    }
+}
+
+impl Default for IncrementRules {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            default: default_increment(),
+            initial_development: true,
+        }
+    }
+}
+
+/// The increment required by commits of a type, and optionally a scope.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IncrementRule {
+    /// the type of the commit, such as `feat` or `perf`
+    pub commit_type: String,
+    /// the scope of the commit. Commits of any scope are matched when it isn't given.
+    #[serde(default)]
+    pub scope: Option<String>,
+    pub increment: Increment,
+}
+
+/// The component of the version that a commit requires to be incremented, ordered from the
+/// smallest to the largest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Increment {
+    /// the commit doesn't require a release
+    None,
+    Patch,
+    Minor,
+    Major,
+}
+
+fn default_increment() -> Increment {
+    Increment::Patch
+}
+
+/// How commits are grouped into the sections of changelog entries.
+/// Sections are listed in the order they are configured, followed by a section for each commit
+/// type without one, in alphabetical order, unless `unlisted` is `Hide`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangelogConfig {
+    #[serde(default = "default_sections")]
+    pub sections: Vec<ChangelogSection>,
+    /// whether commits of types without a section are listed
+    #[serde(default)]
+    pub unlisted: UnlistedCommits,
+    /// commits that are left out of the changelog, such as the `chore(version)` commits made by
+    /// bomper. When packages are versioned independently, they also don't require a release.
+    #[serde(default = "default_hidden")]
+    pub hidden: Vec<CommitMatcher>,
+    /// a minijinja template file that renders changelog entries in place of the built-in one.
+    /// The entry is available as `entry`, with the fields:
+    /// - `version`, `date`, and `description`
+    /// - `breaking_changes`, the breaking commits
+    /// - `links.release`, a link to the release if the repository has a remote
+    /// - `sections`, each with a `title` and a list of `commits`
+    ///
+    /// Commits can be rendered with `{% include "changelog_commit" %}`, which uses the commit
+    /// template for the `commit` variable.
+    #[serde(default)]
+    pub template: Option<PathBuf>,
+    /// a minijinja template file that renders each commit in place of the built-in one.
+    /// The commit is available as `commit`, with the fields:
+    /// - `type`, `scope`, `summary`, `body`, and `breaking`
+    /// - `breaking_description`, the text of the `BREAKING CHANGE` footers
+    /// - `footers`, each with a `token` and `content`
+    /// - `hash` and `short_hash`
+    /// - `author`, and `username` from the `authors` configuration
+    /// - `links.commit` and `links.author`, if the repository has a remote
+    #[serde(default)]
+    pub commit_template: Option<PathBuf>,
+    /// whether references like `#42` in commit summaries and footers are linked to the issues and
+    /// pull requests of the repository's remote
+    #[serde(default = "enabled")]
+    pub forge_references: bool,
+    /// patterns of references to external trackers that are linked in commit summaries and
+    /// footers, such as `JIRA-123`
+    #[serde(default)]
+    pub references: Vec<ReferencePattern>,
+}
+
+impl ChangelogConfig {
+    /// Whether a commit is hidden from the changelog, regardless of its type's section.
+    #[must_use]
+    pub fn is_hidden(&self, commit: &ConventionalCommit) -> bool {
+        self.hidden.iter().any(|matcher| matcher.matches(commit))
+    }
+
+    /// Determines the title of the section a commit is listed in, or `None` if it is left out.
+    #[must_use]
+    pub fn section_of(&self, commit: &ConventionalCommit) -> Option<String> {
+        if self.is_hidden(commit) {
+            return None;
+        }
+
+        let section = self.sections.iter().find(|section| {
+            section
+                .commit_types
+                .iter()
+                .any(|commit_type| CommitType::from(commit_type.as_str()) == commit.commit_type)
+        });
+        match (section, self.unlisted) {
+            (Some(section), _) => Some(section.title.clone()),
+            (None, UnlistedCommits::Show) => Some(display_commit_type(&commit.commit_type)),
+            (None, UnlistedCommits::Hide) => None,
+        }
+    }
+
+    /// The position of a section in the changelog, given its title.
+    /// Sections without a configuration are placed after the configured ones.
+    #[must_use]
+    pub fn position(&self, title: &str) -> Option<usize> {
+        self.sections
+            .iter()
+            .position(|section| section.title == title)
+    }
+}
+
+impl Default for ChangelogConfig {
+    fn default() -> Self {
+        Self {
+            sections: default_sections(),
+            unlisted: UnlistedCommits::default(),
+            hidden: default_hidden(),
+            template: None,
+            commit_template: None,
+            forge_references: true,
+            references: Vec::new(),
+        }
+    }
+}
+
+/// A section of changelog entries, listing the commits of one or more types.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangelogSection {
+    /// the heading of the section
+    pub title: String,
+    /// the commit types listed in the section, such as `feat` or a custom type like `security`
+    pub commit_types: Vec<String>,
+}
+
+/// Links references to an external tracker, such as `(pattern: "\\b[A-Z]+-\\d+\\b", url:
+/// "https://jira.example.com/browse/$0")`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReferencePattern {
+    /// a regex matching the references
+    pub pattern: String,
+    /// the link of a reference, where `$0` is the whole reference and `$1`, `$2`, or `${name}`
+    /// are its capture groups
+    pub url: String,
+}
+
+/// Whether commits of types without a configured section are listed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnlistedCommits {
+    /// list them in a section named after their type
+    #[default]
+    Show,
+    /// leave them out of the changelog
+    Hide,
+}
+
+/// Matches commits of a type, and optionally a scope.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitMatcher {
+    pub commit_type: String,
+    /// the scope of the commit. Commits of any scope are matched when it isn't given.
+    #[serde(default)]
+    pub scope: Option<String>,
+}
+
+impl CommitMatcher {
+    #[must_use]
+    pub fn matches(&self, commit: &ConventionalCommit) -> bool {
+        CommitType::from(self.commit_type.as_str()) == commit.commit_type
+            && (self.scope.is_none() || self.scope == commit.scope)
+    }
+}
+
+fn default_sections() -> Vec<ChangelogSection> {
+    [
+        "feat", "fix", "perf", "refactor", "revert", "docs", "test", "build", "ci", "style",
+        "chore",
+    ]
+    .into_iter()
+    .map(|commit_type| ChangelogSection {
+        title: display_commit_type(&CommitType::from(commit_type)),
+        commit_types: vec![commit_type.to_string()],
+    })
+    .collect()
+}
+
+fn default_hidden() -> Vec<CommitMatcher> {
+    vec![CommitMatcher {
+        commit_type: "chore".to_string(),
+        scope: Some("version".to_string()),
+    }]
+}
+
+/// Which version tags are considered when finding the latest version.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TagSelection {
+    /// only tags of commits in the history of HEAD, so that a maintenance branch isn't affected by
+    /// the releases of other branches
+    #[default]
+    Reachable,
+    /// every tag in the repository
+    All,
+}
+
+/// How the commits in a range of history are selected.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitPolicy {
+    #[serde(default)]
+    pub non_conventional: NonConventionalCommits,
+    #[serde(default)]
+    pub merges: MergeCommits,
+}
+
+/// How commits whose messages don't follow the conventional commit format are handled.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NonConventionalCommits {
+    /// fail, reporting the commit
+    #[default]
+    Error,
+    /// leave the commits out, listing them in a warning
+    Skip,
+    /// include the commits in the changelog's `other` section
+    Other,
+}
+
+/// How merge commits are handled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MergeCommits {
+    /// include merge commits along with the commits of the merged branches
+    #[default]
+    Include,
+    /// leave out merge commits, keeping the commits of the merged branches
+    Skip,
+    /// only follow the first parent of merge commits, leaving out the commits of the merged
+    /// branches. This suits histories where each merge commit describes its whole branch.
+    FirstParent,
 }
 
 /// Reads from the Cargo.lock file to determine which packages to bump versions for.
@@ -40,4 +620,93 @@ pub enum CargoReplaceMode {
     Autodetect,
     /// Manually specify package names
     Packages(Vec<String>),
+    /// version each package in the workspace on its own.
+    /// Each package has its own tags (named by the tag template, `{package}@{version}` by
+    /// default), is incremented based on the commits that changed its directory, and has a
+    /// changelog in its directory. Only packages that changed are bumped, and the requirements of
+    /// packages depending on them are updated. Other replacers are not applied in this mode.
+    /// `bomp raw-bump` is rejected, since the packages don't share an old and new version.
+    Independent,
+}
+
+/// Reads package.json files to determine which packages to bump versions for.
+/// Workspaces are discovered from the root package.json (npm and yarn) or pnpm-workspace.yaml (pnpm).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NpmReplaceMode {
+    /// update the root package and every package in the workspace
+    Autodetect,
+    /// Manually specify package names
+    Packages(Vec<String>),
+}
+
+/// Updates the version of Python projects through their pyproject.toml files.
+/// Both `[project].version` and `[tool.poetry].version` are supported.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PythonConfig {
+    /// the pyproject.toml files to update, defaulting to the one in the project root
+    #[serde(default = "default_pyprojects")]
+    pub pyprojects: Vec<PathBuf>,
+    /// a module containing a `__version__ = "..."` assignment to update as well
+    #[serde(default)]
+    pub version_module: Option<PathBuf>,
+}
+
+impl Default for PythonConfig {
+    fn default() -> Self {
+        Self {
+            pyprojects: default_pyprojects(),
+            version_module: None,
+        }
+    }
+}
+
+fn default_pyprojects() -> Vec<PathBuf> {
+    vec![PathBuf::from("pyproject.toml")]
+}
+
+/// Appends build metadata to the new version, such as `1.2.0+20240101.abc1234`.
+/// Since some registries strip or reject build metadata, where it is used can be controlled
+/// separately; by default it is used everywhere.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildMetadataConfig {
+    /// the identifiers of the metadata, joined with `.`
+    pub parts: Vec<BuildMetadataPart>,
+    /// whether the versions written to files include the metadata.
+    /// The next bump finds the old version through the latest tag, so this can only be enabled
+    /// together with `tag`. When it is disabled, the metadata of the latest tag is ignored when
+    /// finding the old version in files, so `tag` can be enabled on its own.
+    #[serde(default = "enabled")]
+    pub files: bool,
+    /// whether the name of the new tag includes the metadata
+    #[serde(default = "enabled")]
+    pub tag: bool,
+    /// whether the heading of the changelog entry includes the metadata
+    #[serde(default = "enabled")]
+    pub changelog: bool,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BuildMetadataPart {
+    /// the abbreviated hash of HEAD before the release, which is the parent of the `chore(version)`
+    /// commit that is tagged
+    CommitHash,
+    /// the current local date, formatted as `YYYYMMDD`
+    Date,
+    /// the value of an environment variable, such as a CI build number
+    Env(String),
+}
+
+/// Updates the `version` attribute of derivations in Nix files.
+/// Only derivations with a matching `pname` are changed, so versions of other derivations (such
+/// as a `fetchurl` source) are left alone.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NixConfig {
+    /// the Nix files containing the derivations
+    pub files: Vec<PathBuf>,
+    /// the `pname` of each derivation to update
+    pub pnames: Vec<String>,
 }
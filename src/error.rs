@@ -17,8 +17,6 @@ pub enum Error {
     #[error(transparent)]
     CargoLock(#[from] cargo_lock::Error),
     #[error(transparent)]
-    CargoToml(#[from] cargo_toml::Error),
-    #[error(transparent)]
     SemverParse(#[from] semver::Error),
     #[error(transparent)]
     GixCommit(#[from] gix::commit::Error),
@@ -39,6 +37,12 @@ pub enum Error {
     #[error(transparent)]
     GixRefEdit(#[from] gix::reference::edit::Error),
     #[error(transparent)]
+    GixPeel(#[from] gix::reference::peel::Error),
+    #[error(transparent)]
+    GixTag(#[from] gix::tag::Error),
+    #[error(transparent)]
+    GixConfigTime(#[from] gix::config::time::Error),
+    #[error(transparent)]
     GixReferenceHeadId(#[from] gix::reference::head_id::Error),
     #[error(transparent)]
     GixRemoteFindExisting(#[from] gix::remote::find::existing::Error),
@@ -50,6 +54,11 @@ pub enum Error {
     GixWalk(#[from] gix::revision::walk::Error),
     #[error(transparent)]
     ConventialCommitParse(#[from] conventional_commit_parser::error::ParseError),
+    #[error("commit {commit} is not a conventional commit: {source}")]
+    NonConventionalCommit {
+        commit: gix::ObjectId,
+        source: conventional_commit_parser::error::ParseError,
+    },
     #[error(transparent)]
     MiniJinja(#[from] minijinja::Error),
     #[error(transparent)]
@@ -62,6 +71,18 @@ pub enum Error {
     TomlSerialize(#[from] toml::ser::Error),
     #[error("invalid toml: {0}")]
     TomlDeserialize(#[from] toml::de::Error),
+    #[error("invalid toml: {0}")]
+    TomlEdit(#[from] toml_edit::TomlError),
+    #[error("invalid json: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid yaml: {0}")]
+    Yaml(#[from] saphyr_parser::ScanError),
+    #[error("invalid nix: {0}")]
+    NixParse(#[from] rnix::parser::ParseError),
+    #[error(transparent)]
+    Glob(#[from] globset::Error),
+    #[error(transparent)]
+    Walk(#[from] ignore::Error),
 
     #[error("EDITOR environment variable not set")]
     EditorNotSet,
@@ -75,8 +96,87 @@ pub enum Error {
     TagError,
     #[error("version '{0}' was not found")]
     VersionNotFound(semver::Version),
+    #[error("version '{0}' is not a pre-release")]
+    NotPrerelease(semver::Version),
+    #[error("invalid pre-release identifier: {0}")]
+    InvalidPrereleaseIdentifier(String),
+    #[error("pre-release {new} would not follow the current version {current}")]
+    PrereleaseRegression { current: String, new: String },
+    #[error("environment variable `{0}` is not set")]
+    MissingEnvVar(String),
+    #[error("invalid version: {0}")]
+    InvalidVersion(String),
+    #[error("invalid calendar version format: {0}")]
+    InvalidCalverFormat(String),
+    #[error("calendar version {0} was already released in this period, and the format has no `PATCH` to increment")]
+    CalverExhausted(String),
+    #[error("invalid tag template `{0}`: it must contain `{{version}}` once, and no placeholders other than `{{package}}`")]
+    InvalidTagTemplate(String),
+    #[error("tag template `{0}` contains `{{package}}`, but no package is configured")]
+    MissingTagPackage(String),
+    #[error(
+        "tag template `{0}` must contain `{{package}}` when packages are versioned independently"
+    )]
+    PackageTagTemplate(String),
+    #[error("cargo packages are versioned independently, so they can't be moved to a single version; use `bomp bump` instead")]
+    SharedIndependentVersion,
+    #[error("{0} inherits its version from the workspace, so it can't be versioned independently")]
+    InheritedCargoVersion(cargo_metadata::camino::Utf8PathBuf),
+    #[error("failed to read changelog template {path}: {source}")]
+    ChangelogTemplate {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
     #[error("changelog does not contain marker character")]
     ChangelogMarker,
+    #[error("pattern `{0}` did not match any files")]
+    NoGlobMatches(String),
+    #[error("invalid value path: {0}")]
+    InvalidValuePath(String),
+    #[error("{selector} did not match any value in {path}")]
+    SelectorNotFound {
+        path: std::path::PathBuf,
+        selector: crate::config::Selector,
+    },
+    #[error("{selector} matched {count} values in {path}, expected exactly one")]
+    SelectorAmbiguous {
+        path: std::path::PathBuf,
+        selector: crate::config::Selector,
+        count: usize,
+    },
+    #[error("{selector} in {path} is `{found}`, expected `{expected}`")]
+    SelectorValueMismatch {
+        path: std::path::PathBuf,
+        selector: crate::config::Selector,
+        expected: String,
+        found: String,
+    },
+    #[error("{rule} replaced {count} values in {path}, expected {expect}")]
+    UnexpectedReplacementCount {
+        path: std::path::PathBuf,
+        rule: String,
+        expect: crate::config::Expect,
+        count: usize,
+    },
+    #[error("build metadata can only be written to files when it is also part of the tag, since the next bump finds the version in files through the tag")]
+    BuildMetadataFilesWithoutTag,
+    #[error("a file rule can only set one of `search_value`, `yaml_paths`, `selector`, and `regex`, but it sets {0}")]
+    ConflictingFileRule(String),
+    #[error("{selector} always replaces exactly one value, so it can't expect {expect}")]
+    SelectorExpectation {
+        selector: crate::config::Selector,
+        expect: crate::config::Expect,
+    },
+    #[error("regex `{0}` has no `version` capture group")]
+    MissingCaptureGroup(String),
+    #[error("captured `{found}` in {path}, expected `{expected}`")]
+    CapturedValueMismatch {
+        path: std::path::PathBuf,
+        expected: String,
+        found: String,
+    },
+    #[error("no `__version__` assignment found in {0}")]
+    VersionAssignmentNotFound(std::path::PathBuf),
 }
 
 impl std::fmt::Debug for Error {
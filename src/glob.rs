@@ -0,0 +1,65 @@
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::path::PathBuf;
+
+use crate::error::Result;
+
+/// Finds all paths beneath the current directory that match any of the given glob patterns.
+/// Patterns prefixed with `!` exclude the paths they match instead.
+/// Paths ignored by git (e.g. through a `.gitignore`) are skipped.
+///
+/// The returned paths are sorted and relative to the current directory.
+///
+/// # Errors
+///
+/// - a pattern is not a valid glob
+/// - the directory tree cannot be read
+pub fn expand<S: AsRef<str>>(patterns: &[S]) -> Result<Vec<PathBuf>> {
+    let mut include = GlobSetBuilder::new();
+    let mut exclude = GlobSetBuilder::new();
+    for pattern in patterns {
+        let pattern = pattern.as_ref();
+        if let Some(pattern) = pattern.strip_prefix('!') {
+            exclude.add(build_glob(pattern)?);
+        } else {
+            include.add(build_glob(pattern)?);
+        }
+    }
+    let include = include.build()?;
+    let exclude = exclude.build()?;
+
+    let walker = ignore::WalkBuilder::new(".")
+        .hidden(false)
+        .require_git(false)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build();
+
+    let mut paths = Vec::new();
+    for entry in walker {
+        let entry = entry?;
+        let path = entry.path().strip_prefix(".")?;
+        if is_match(path, &include, &exclude) {
+            paths.push(path.to_path_buf());
+        }
+    }
+    paths.sort();
+
+    Ok(paths)
+}
+
+/// Whether the path contains glob syntax, meaning it should be expanded rather than used as is.
+#[must_use]
+pub fn is_pattern(path: &str) -> bool {
+    path.contains(['*', '?', '[', '{'])
+}
+
+fn is_match(path: &std::path::Path, include: &GlobSet, exclude: &GlobSet) -> bool {
+    !path.as_os_str().is_empty() && include.is_match(path) && !exclude.is_match(path)
+}
+
+fn build_glob(pattern: &str) -> Result<globset::Glob> {
+    // A leading `./` or trailing `/` would never match the walked paths
+    let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+    let glob = GlobBuilder::new(pattern).literal_separator(true).build()?;
+
+    Ok(glob)
+}
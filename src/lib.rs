@@ -2,6 +2,7 @@
 pub mod changelog;
 pub mod config;
 pub mod error;
+pub mod glob;
 pub mod replacers;
 pub mod versioning;
 
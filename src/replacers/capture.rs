@@ -0,0 +1,78 @@
+use std::{fs, path::PathBuf};
+
+use super::{file, replace_ranges, ContentReplacer, VersionReplacement};
+use crate::config::RegexReplace;
+use crate::error::{Error, Result};
+use crate::replacers::ReplacementBuilder;
+
+/// The name of the capture group that contains the version
+const GROUP: &str = "version";
+
+/// Replaces the text captured by the `version` group of a user supplied regex.
+/// Only the captured span is changed, so the regex can match as much context as is needed to find
+/// the right value.
+pub struct Replacer {
+    path: PathBuf,
+    regex: regex::Regex,
+    verify: bool,
+    versions: VersionReplacement,
+}
+
+impl Replacer {
+    /// # Errors
+    ///
+    /// - the pattern is not a valid regex
+    /// - the pattern has no `version` capture group
+    pub fn new(path: PathBuf, config: &RegexReplace, versions: VersionReplacement) -> Result<Self> {
+        let regex = regex::Regex::new(&config.pattern)?;
+        if !regex.capture_names().any(|name| name == Some(GROUP)) {
+            return Err(Error::MissingCaptureGroup(config.pattern.clone()));
+        }
+
+        Ok(Self {
+            path,
+            regex,
+            verify: config.verify,
+            versions,
+        })
+    }
+}
+
+impl ReplacementBuilder for Replacer {
+    fn determine_replacements(self) -> Result<Option<Vec<file::Replacer>>> {
+        let contents = fs::read_to_string(&self.path)?;
+        let (contents, count) = self.replace_contents(&contents)?;
+        if count == 0 {
+            return Err(Error::InvalidReplacementCount(0));
+        }
+        let replacer = file::Replacer::with_contents(self.path, contents.as_bytes())?;
+
+        Ok(Some(vec![replacer]))
+    }
+}
+
+impl ContentReplacer for Replacer {
+    fn replace_contents(&self, contents: &str) -> Result<(String, usize)> {
+        let mut ranges = Vec::new();
+        for captures in self.regex.captures_iter(contents) {
+            // the group may be optional, in which case there is nothing to replace
+            let Some(capture) = captures.name(GROUP) else {
+                continue;
+            };
+            if self.verify && capture.as_str() != self.versions.old_version {
+                return Err(Error::CapturedValueMismatch {
+                    path: self.path.clone(),
+                    expected: self.versions.old_version.clone(),
+                    found: capture.as_str().to_string(),
+                });
+            }
+            ranges.push(capture.range());
+        }
+
+        let new_version = &self.versions.new_version;
+        Ok(replace_ranges(
+            contents,
+            ranges.into_iter().map(|range| (range, new_version)),
+        ))
+    }
+}
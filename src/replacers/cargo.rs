@@ -1,31 +1,79 @@
-use anyhow::anyhow;
-use cargo_metadata::camino::Utf8Path;
+use cargo_metadata::camino::{Utf8Path, Utf8PathBuf};
 use std::path::Path;
 use std::{io::prelude::*, path::PathBuf, str::FromStr};
 
-use super::file;
 use super::VersionReplacement;
+use super::{file, toml};
 use crate::config::CargoReplaceMode;
 use crate::error::{Error, Result};
 use crate::replacers::ReplacementBuilder;
 
-/// Replaces all instances of a given value with a new one.
-/// This is a somewhat naive implementation, but it works.
-/// The area surrounding the value will be checked for matches in the supplied regex
+/// The tables in a manifest (or a `target.<cfg>` table) that list dependencies
+const DEPENDENCY_KINDS: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// Updates the versions of packages in a cargo workspace.
+/// The `version` of each bumped package is edited in its Cargo.toml with `toml_edit`, so the rest
+/// of the manifest stays intact. The requirements of other packages on the bumped packages and the
+/// entries in Cargo.lock are updated as well.
+///
+/// Packages either share a version, moving from one old version to one new version along with
+/// `workspace.package.version`, or are bumped independently, each between its own versions.
 pub struct Replacer {
     lock_path: PathBuf,
-    versions: VersionReplacement,
-    replace_mode: CargoReplaceMode,
+    bumps: Bumps,
+}
+
+/// The versions to move the workspace's packages between.
+enum Bumps {
+    /// every selected package moves from the same old version to the same new version
+    Shared {
+        versions: VersionReplacement,
+        replace_mode: CargoReplaceMode,
+    },
+    /// each package moves between its own versions
+    Independent(Vec<PackageBump>),
+}
+
+/// A new version for a single package in the workspace.
+#[derive(Clone, Debug)]
+pub struct PackageBump {
+    pub name: String,
+    pub versions: VersionReplacement,
+}
+
+/// A member of the cargo workspace.
+#[derive(Clone, Debug)]
+pub struct WorkspacePackage {
+    pub name: String,
+    pub version: semver::Version,
+    /// the directory containing the package's manifest, relative to the workspace root
+    pub directory: PathBuf,
 }
 
 impl Replacer {
+    /// Creates a replacer that moves the selected packages from one version to another.
+    /// [`CargoReplaceMode::Independent`] can't be used here, since its packages don't share a
+    /// version, see [`Replacer::independent`].
     #[must_use]
     pub fn new(versions: VersionReplacement, replace_mode: CargoReplaceMode) -> Self {
         Self {
             // TODO: This may need to be specified, or detected
             lock_path: PathBuf::from("Cargo.lock"),
-            versions,
-            replace_mode,
+            bumps: Bumps::Shared {
+                versions,
+                replace_mode,
+            },
+        }
+    }
+
+    /// Creates a replacer that bumps each of the given packages to its own version.
+    /// Packages that aren't listed keep their version, but their requirements on the bumped
+    /// packages are still updated.
+    #[must_use]
+    pub fn independent(bumps: Vec<PackageBump>) -> Self {
+        Self {
+            lock_path: PathBuf::from("Cargo.lock"),
+            bumps: Bumps::Independent(bumps),
         }
     }
 }
@@ -40,27 +88,39 @@ impl ReplacementBuilder for Replacer {
         // Read in the file
         let mut lockfile = cargo_lock::Lockfile::load(&self.lock_path)?;
 
-        let packages = match &self.replace_mode {
-            CargoReplaceMode::Autodetect => metadata.packages,
-            CargoReplaceMode::Packages(packages) => list_packages(&metadata, packages),
+        // the shared versions also apply to the `workspace.package` version
+        let (bumps, workspace_versions) = match self.bumps {
+            Bumps::Shared {
+                versions,
+                replace_mode,
+            } => {
+                let packages = match &replace_mode {
+                    CargoReplaceMode::Autodetect => metadata.packages.clone(),
+                    CargoReplaceMode::Packages(packages) => list_packages(&metadata, packages),
+                    CargoReplaceMode::Independent => return Err(Error::SharedIndependentVersion),
+                };
+                let bumps = packages
+                    .into_iter()
+                    .map(|package| PackageBump {
+                        name: package.name,
+                        versions: versions.clone(),
+                    })
+                    .collect::<Vec<_>>();
+                (bumps, Some(versions))
+            }
+            Bumps::Independent(bumps) => (bumps, None),
         };
 
-        let new_version = cargo_lock::Version::from_str(&self.versions.new_version)?;
-        let old_version = cargo_lock::Version::from_str(&self.versions.old_version)?;
-
-        let package_names = packages
-            .iter()
-            .map(|package| package.name.as_str().to_string())
-            .collect::<Vec<String>>();
-
         // update cargo.lock with new versions of packages
-        lockfile.packages.iter_mut().for_each(|package| {
-            let package_name = package.name.as_str().to_string();
-
-            if package_names.contains(&package_name) && package.version == old_version {
-                package.version = new_version.clone();
+        for bump in &bumps {
+            let old_version = cargo_lock::Version::from_str(&bump.versions.old_version)?;
+            let new_version = cargo_lock::Version::from_str(&bump.versions.new_version)?;
+            for package in &mut lockfile.packages {
+                if package.name.as_str() == bump.name && package.version == old_version {
+                    package.version = new_version.clone();
+                }
             }
-        });
+        }
 
         let new_data = lockfile.to_string().into_bytes();
 
@@ -77,33 +137,45 @@ impl ReplacementBuilder for Replacer {
             temp_file,
         });
 
-        // Update each package's Cargo.toml with the new version
-        for package in packages {
-            let replacer =
-                update_package(&package, workspace_root, &self.lock_path, &self.versions)?;
-            if let Some(replacer) = replacer {
-                replacers.push(replacer);
-            };
-        }
-
-        // Now, we need to update the Cargo.toml in the workspace root
-        // This can be a bit more complicated, because the workspace root may be one of the packages
-        // (specifically if there is a single package in the workspace)
-        // In this case we need to find the package that is the workspace root, and if we're already
-        // updating that package
-
-        // First, check to see if we've updated the `Cargo.toml` in the workspace root
-        let root_toml_path = workspace_root.join("Cargo.toml");
-        let found_workspace_root = replacers
+        // Update each Cargo.toml in the workspace with the new version.
+        // Every member is visited since packages that are not being bumped may still depend on
+        // ones that are.
+        // The workspace root may not be a package, but it can still hold `workspace.package` and
+        // `workspace.dependencies` entries that need to be updated.
+        // Manifest paths are relative to the workspace root
+        let mut manifest_paths = metadata
+            .packages
             .iter()
-            .find(|replacer| replacer.path == root_toml_path);
+            .map(|package| {
+                Ok(package
+                    .manifest_path
+                    .strip_prefix(workspace_root)?
+                    .to_owned())
+            })
+            .collect::<Result<Vec<Utf8PathBuf>>>()?;
+        let root_toml_path = Utf8PathBuf::from("Cargo.toml");
+        if !manifest_paths.contains(&root_toml_path) {
+            manifest_paths.push(root_toml_path);
+        }
 
-        if found_workspace_root.is_none() {
-            // We haven't updated the workspace root, so we need to do that now
-            let replacer = update_workspace_root(workspace_root, &self.versions)?;
+        for cargo_toml_path in manifest_paths {
+            let manifest_path = workspace_root.join(&cargo_toml_path);
+            let own_versions = metadata
+                .packages
+                .iter()
+                .find(|package| package.manifest_path == manifest_path)
+                .and_then(|package| bumps.iter().find(|bump| bump.name == package.name))
+                .map(|bump| &bump.versions);
+            let replacer = update_manifest(
+                &cargo_toml_path,
+                own_versions,
+                workspace_versions.as_ref(),
+                &bumps,
+                &self.lock_path,
+            )?;
             if let Some(replacer) = replacer {
                 replacers.push(replacer);
-            };
+            }
         }
 
         Ok(Some(replacers))
@@ -123,6 +195,30 @@ fn list_packages(
         .collect()
 }
 
+/// Lists the members of the cargo workspace in the current directory.
+///
+/// # Errors
+///
+/// - `cargo metadata` fails
+pub fn workspace_packages() -> Result<Vec<WorkspacePackage>> {
+    let metadata = get_workspace_metadata()?;
+    metadata
+        .packages
+        .iter()
+        .map(|package| {
+            let manifest_path = package
+                .manifest_path
+                .strip_prefix(&metadata.workspace_root)?;
+            let directory = manifest_path.parent().unwrap_or(Utf8Path::new(""));
+            Ok(WorkspacePackage {
+                name: package.name.clone(),
+                version: package.version.clone(),
+                directory: directory.into(),
+            })
+        })
+        .collect()
+}
+
 /// Retrieves the metadata for the current workspace.
 fn get_workspace_metadata() -> Result<cargo_metadata::Metadata> {
     let mut metadata_cmd = cargo_metadata::MetadataCommand::new();
@@ -134,38 +230,58 @@ fn get_workspace_metadata() -> Result<cargo_metadata::Metadata> {
     Ok(metadata)
 }
 
-/// Updates the workspace root's Cargo.toml with the new version
-fn update_workspace_root(
-    workspace_root: &Utf8Path,
-    versions: &VersionReplacement,
+/// Updates a Cargo.toml in the workspace with the new versions.
+/// This covers the package's own version (when `own_versions` is given), the `workspace.package`
+/// version (when `workspace_versions` is given), and any dependency requirements on the packages
+/// being bumped.
+fn update_manifest(
+    cargo_toml_path: &Utf8Path,
+    own_versions: Option<&VersionReplacement>,
+    workspace_versions: Option<&VersionReplacement>,
+    bumps: &[PackageBump],
+    lock_path: &Path,
 ) -> Result<Option<file::Replacer>> {
-    let cargo_toml_path = workspace_root.join("Cargo.toml");
-    let cargo_toml_path = cargo_toml_path.strip_prefix(workspace_root)?;
-    let cargo_toml_content = std::fs::read(cargo_toml_path)?;
+    let cargo_toml_content = std::fs::read_to_string(cargo_toml_path)?;
+    let mut cargo_toml = cargo_toml_content.parse::<toml_edit::DocumentMut>()?;
+    let mut changed = false;
+
+    if let Some(versions) = own_versions {
+        let Some(toml_package) = cargo_toml
+            .get_mut("package")
+            .and_then(toml_edit::Item::as_table_like_mut)
+        else {
+            return Err(Error::InvalidCargoToml(cargo_toml_path.into()));
+        };
 
-    let mut cargo_toml = cargo_toml::Manifest::from_slice(&cargo_toml_content)?;
+        // If the version is inherited, it is not a string and will not be replaced.
+        // That's only correct when the workspace version is being bumped along with it.
+        let inherited = toml_package
+            .get("version")
+            .is_some_and(|version| version.as_str().is_none());
+        if inherited && workspace_versions.is_none() {
+            return Err(Error::InheritedCargoVersion(cargo_toml_path.into()));
+        }
+        changed |= toml_package
+            .get_mut("version")
+            .is_some_and(|version| toml::replace_version(version, versions));
+    }
 
-    let Some(ref mut workspace) = cargo_toml.workspace else {
-        return Ok(None);
-    };
-    let Some(ref mut workspace_package) = workspace.package else {
-        return Ok(None);
-    };
+    if let Some(versions) = workspace_versions {
+        changed |= modify_workspace_root(&mut cargo_toml, versions);
+    }
+    changed |= update_dependencies(cargo_toml.as_table_mut(), bumps);
 
-    if workspace_package.version != Some(versions.old_version.clone()) {
+    if !changed {
         return Ok(None);
     }
-    workspace_package.version = Some(versions.new_version.clone());
 
     let temp_file = tempfile::NamedTempFile::new_in(
-        (workspace_root)
+        (lock_path)
             .parent()
-            .ok_or_else(|| Error::Other(anyhow!("Invalid path: {:?}", workspace_root)))?,
+            .ok_or_else(|| Error::InvalidPath((lock_path).to_path_buf()))?,
     )?;
     let mut file = temp_file.as_file();
-
-    let data = toml::to_string(&cargo_toml)?;
-    file.write_all(data.as_bytes())?;
+    file.write_all(cargo_toml.to_string().as_bytes())?;
 
     Ok(Some(file::Replacer {
         path: cargo_toml_path.into(),
@@ -173,71 +289,102 @@ fn update_workspace_root(
     }))
 }
 
-/// Updates a package's Cargo.toml with the new version
-fn update_package(
-    package: &cargo_metadata::Package,
-    workspace_root: &Utf8Path,
-    lock_path: &Path,
+/// Updates the `workspace.package.version` key, returning whether it was changed.
+fn modify_workspace_root(
+    cargo_toml: &mut toml_edit::DocumentMut,
     versions: &VersionReplacement,
-) -> Result<Option<file::Replacer>> {
-    let cargo_toml_path = package.manifest_path.clone();
-    let cargo_toml_path = cargo_toml_path.strip_prefix(workspace_root)?;
-    let cargo_toml_content = std::fs::read(cargo_toml_path)?;
-
-    let mut cargo_toml = cargo_toml::Manifest::from_slice(&cargo_toml_content)?;
-    // let mut cargo_toml = cargo_toml::Manifest::from_path(&cargo_toml_path)?;
-
-    {
-        let Some(ref mut toml_package) = cargo_toml.package else {
-            return Err(Error::InvalidCargoToml(cargo_toml_path.into()));
-        };
-
-        let file_version = match &mut toml_package.version {
-            // If the version is inherited, we don't need to do anything
-            cargo_toml::Inheritable::Inherited { .. } => return Ok(None),
-            cargo_toml::Inheritable::Set(value) => value,
-        };
+) -> bool {
+    toml::get_mut(
+        cargo_toml.as_table_mut(),
+        &["workspace", "package", "version"],
+    )
+    .is_some_and(|version| toml::replace_version(version, versions))
+}
 
-        if file_version != &versions.old_version {
-            return Ok(None);
+/// Updates the version requirements of every dependency on one of the bumped packages.
+/// This includes normal, dev, build, and target-specific dependencies as well as the
+/// `workspace.dependencies` table.
+fn update_dependencies(cargo_toml: &mut toml_edit::Table, bumps: &[PackageBump]) -> bool {
+    let mut changed = false;
+
+    let mut dependency_tables: Vec<&mut dyn toml_edit::TableLike> = Vec::new();
+    for (key, item) in cargo_toml.iter_mut() {
+        match key.get() {
+            "target" => {
+                let Some(targets) = item.as_table_like_mut() else {
+                    continue;
+                };
+                for (_, target) in targets.iter_mut() {
+                    let Some(target) = target.as_table_like_mut() else {
+                        continue;
+                    };
+                    for (kind, dependencies) in target.iter_mut() {
+                        if DEPENDENCY_KINDS.contains(&kind.get()) {
+                            dependency_tables.extend(dependencies.as_table_like_mut());
+                        }
+                    }
+                }
+            }
+            "workspace" => {
+                dependency_tables.extend(
+                    item.as_table_like_mut()
+                        .and_then(|workspace| workspace.get_mut("dependencies"))
+                        .and_then(toml_edit::Item::as_table_like_mut),
+                );
+            }
+            kind if DEPENDENCY_KINDS.contains(&kind) => {
+                dependency_tables.extend(item.as_table_like_mut());
+            }
+            _ => {}
         }
-
-        file_version.clone_from(&versions.new_version);
     }
 
-    // check if this is a workspace root
-    // if it is, we need to update the workspace root's Cargo.toml
-    let workspace_root_toml_path = workspace_root.join("Cargo.toml");
-    if cargo_toml_path == workspace_root_toml_path {
-        modify_workspace_root(&mut cargo_toml, versions);
-    }
+    for dependencies in dependency_tables {
+        for (name, dependency) in dependencies.iter_mut() {
+            // Dependencies given as a plain version string can't be path dependencies
+            let Some(dependency) = dependency.as_table_like_mut() else {
+                continue;
+            };
 
-    let temp_file = tempfile::NamedTempFile::new_in(
-        (lock_path)
-            .parent()
-            .ok_or_else(|| Error::InvalidPath((lock_path).to_path_buf()))?,
-    )?;
-    let mut file = temp_file.as_file();
+            // The dependency may be renamed, in which case the key isn't the package name
+            let package_name = dependency
+                .get("package")
+                .and_then(toml_edit::Item::as_str)
+                .unwrap_or(name.get());
+            let Some(bump) = bumps.iter().find(|bump| bump.name == package_name) else {
+                continue;
+            };
+            if !dependency.contains_key("path") {
+                continue;
+            }
 
-    let data = toml::to_string(&cargo_toml)?;
-    file.write_all(data.as_bytes())?;
+            changed |= dependency
+                .get_mut("version")
+                .is_some_and(|version| replace_requirement(version, &bump.versions));
+        }
+    }
 
-    Ok(Some(file::Replacer {
-        path: cargo_toml_path.into(),
-        temp_file,
-    }))
+    changed
 }
 
-fn modify_workspace_root(cargo_toml: &mut cargo_toml::Manifest, versions: &VersionReplacement) {
-    let Some(ref mut workspace) = cargo_toml.workspace else {
-        return;
+/// Replaces a version requirement in place if it requires the old version.
+/// The requirement's operator (`=`, `^`, `~`) is kept, so `=0.1.0` becomes `=0.2.0`.
+fn replace_requirement(item: &mut toml_edit::Item, versions: &VersionReplacement) -> bool {
+    let Some(value) = item.as_value_mut() else {
+        return false;
     };
-    let Some(ref mut workspace_package) = workspace.package else {
-        return;
+    let Some(requirement) = value.as_str() else {
+        return false;
     };
 
-    if workspace_package.version != Some(versions.old_version.clone()) {
-        return;
+    let operator_len =
+        requirement.len() - requirement.trim_start_matches(['=', '^', '~', ' ']).len();
+    let (operator, version) = requirement.split_at(operator_len);
+    if version != versions.old_version {
+        return false;
     }
-    workspace_package.version = Some(versions.new_version.clone());
+
+    let requirement = format!("{operator}{}", versions.new_version);
+    toml::set_string(value, &requirement);
+    true
 }
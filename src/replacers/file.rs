@@ -1,7 +1,8 @@
 use std::fs;
+use std::io::Write;
 use std::path::PathBuf;
 
-use crate::error::Result;
+use crate::error::{Error, Result};
 
 /// A replacer that contains a temporary file and a path it may be persisted to
 #[derive(Debug)]
@@ -11,15 +12,41 @@ pub struct Replacer {
 }
 
 impl Replacer {
-    /// Persists the pending changes to the file, overwriting its contents
+    /// Creates a replacer that will overwrite the file at `path` with `contents`.
+    /// The permissions of an existing file are kept.
+    ///
+    /// # Errors
+    ///
+    /// - `path` has no parent directory
+    /// - the temporary file cannot be created or written to
+    pub fn with_contents(path: PathBuf, contents: &[u8]) -> Result<Self> {
+        let temp_file = tempfile::NamedTempFile::new_in(
+            path.parent()
+                .ok_or_else(|| Error::InvalidPath(path.clone()))?,
+        )?;
+        let mut file = temp_file.as_file();
+        if let Ok(metadata) = fs::metadata(&path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.write_all(contents)?;
+
+        Ok(Self { path, temp_file })
+    }
+
+    /// Persists the pending changes to the file, overwriting its contents.
+    /// The file is created if it doesn't exist yet.
     ///
     /// # Errors
     ///
-    /// - `self.path` does not exist
     /// - a non-final component of `self.path` is not a directory
     /// - if `self.temp_file` cannot be persisted to `self.path`
     pub fn persist(self) -> Result<()> {
-        let path = fs::canonicalize(&self.path)?;
+        // symlinks are followed so that the file they point to is updated
+        let path = if self.path.try_exists()? {
+            fs::canonicalize(&self.path)?
+        } else {
+            self.path
+        };
         self.temp_file.persist(path)?;
 
         Ok(())
@@ -0,0 +1,185 @@
+use std::ops::Range;
+
+use crate::error::Result;
+
+/// Ensures the document is valid JSON, as [`find_value`] and [`replace_string`] expect, before
+/// it is edited in place.
+///
+/// # Errors
+///
+/// - the document is not valid JSON
+pub fn validate(source: &str) -> Result<()> {
+    serde_json::from_str::<serde::de::IgnoredAny>(source)?;
+    Ok(())
+}
+
+/// Finds the byte range of the value at the given path.
+/// Each segment of the path is either an object key or an array index.
+/// Locating the value in the source allows it to be replaced while the rest of the document stays
+/// byte-for-byte identical, which a round-trip through `serde_json` can't guarantee.
+///
+/// The document is expected to be valid JSON. `None` is returned when the path doesn't exist.
+#[must_use]
+pub fn find_value(source: &str, path: &[&str]) -> Option<Range<usize>> {
+    let mut scanner = Scanner {
+        source: source.as_bytes(),
+        position: 0,
+    };
+    scanner.find(path)
+}
+
+/// Replaces the string at the given path if it is equal to `old_value`.
+/// Returns whether the value was replaced.
+pub fn replace_string(
+    source: &mut String,
+    path: &[&str],
+    old_value: &str,
+    new_value: &str,
+) -> bool {
+    let Some(range) = find_value(source, path) else {
+        return false;
+    };
+    let Ok(current) = serde_json::from_str::<String>(&source[range.clone()]) else {
+        return false;
+    };
+    if current != old_value {
+        return false;
+    }
+
+    let new_value = serde_json::Value::from(new_value).to_string();
+    source.replace_range(range, &new_value);
+    true
+}
+
+struct Scanner<'a> {
+    source: &'a [u8],
+    position: usize,
+}
+
+impl Scanner<'_> {
+    fn find(&mut self, path: &[&str]) -> Option<Range<usize>> {
+        self.skip_whitespace();
+        let Some((segment, rest)) = path.split_first() else {
+            let start = self.position;
+            self.skip_value()?;
+            return Some(start..self.position);
+        };
+
+        match self.peek()? {
+            b'{' => {
+                self.position += 1;
+                loop {
+                    self.skip_whitespace();
+                    if self.peek()? == b'}' {
+                        return None;
+                    }
+                    let key = self.skip_string()?;
+                    let key = serde_json::from_slice::<String>(&self.source[key]).ok()?;
+                    self.skip_whitespace();
+                    self.expect(b':')?;
+                    if key == *segment {
+                        return self.find(rest);
+                    }
+                    self.skip_whitespace();
+                    self.skip_value()?;
+                    self.skip_separator();
+                }
+            }
+            b'[' => {
+                let index = segment.parse::<usize>().ok()?;
+                self.position += 1;
+                for _ in 0..index {
+                    self.skip_whitespace();
+                    if self.peek()? == b']' {
+                        return None;
+                    }
+                    self.skip_value()?;
+                    self.skip_separator();
+                }
+                self.skip_whitespace();
+                if self.peek()? == b']' {
+                    return None;
+                }
+                self.find(rest)
+            }
+            _ => None,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.source.get(self.position).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        if self.peek()? != byte {
+            return None;
+        }
+        self.position += 1;
+        Some(())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|byte| byte.is_ascii_whitespace()) {
+            self.position += 1;
+        }
+    }
+
+    /// Moves past the `,` between two members, if there is one.
+    fn skip_separator(&mut self) {
+        self.skip_whitespace();
+        if self.peek() == Some(b',') {
+            self.position += 1;
+        }
+    }
+
+    /// Moves past a string, returning its range including the quotes.
+    fn skip_string(&mut self) -> Option<Range<usize>> {
+        let start = self.position;
+        self.expect(b'"')?;
+        loop {
+            match self.peek()? {
+                b'\\' => self.position += 2,
+                b'"' => {
+                    self.position += 1;
+                    return Some(start..self.position);
+                }
+                _ => self.position += 1,
+            }
+        }
+    }
+
+    fn skip_value(&mut self) -> Option<()> {
+        match self.peek()? {
+            b'"' => {
+                self.skip_string()?;
+            }
+            b'{' | b'[' => {
+                let mut depth = 0usize;
+                loop {
+                    match self.peek()? {
+                        b'"' => {
+                            self.skip_string()?;
+                            continue;
+                        }
+                        b'{' | b'[' => depth += 1,
+                        b'}' | b']' => depth -= 1,
+                        _ => {}
+                    }
+                    self.position += 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+            }
+            _ => {
+                while self.peek().is_some_and(|byte| {
+                    !matches!(byte, b',' | b'}' | b']') && !byte.is_ascii_whitespace()
+                }) {
+                    self.position += 1;
+                }
+            }
+        }
+
+        Some(())
+    }
+}
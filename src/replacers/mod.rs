@@ -1,9 +1,20 @@
+use std::ops::Range;
+
 use crate::error::Result;
 
+pub mod capture;
 pub mod cargo;
 pub mod file;
+pub mod json;
+pub mod nix;
+pub mod npm;
+pub mod python;
+pub mod rules;
 pub mod search;
+pub mod selector;
 pub mod simple;
+pub mod toml;
+pub mod yaml;
 
 use file::Replacer;
 
@@ -14,6 +25,36 @@ pub trait ReplacementBuilder {
     fn determine_replacements(self) -> Result<Option<Vec<Replacer>>>;
 }
 
+/// Edits the contents of a single file.
+/// Unlike a [`ReplacementBuilder`], several of these can be applied to the same file in one pass.
+pub trait ContentReplacer {
+    /// Returns the new contents along with the number of values that were replaced.
+    ///
+    /// # Errors
+    ///
+    /// - determined by the implementation
+    fn replace_contents(&self, contents: &str) -> Result<(String, usize)>;
+}
+
+/// Replaces each of the given byte ranges of `contents` with its new value.
+/// Ranges that start at the same position are only replaced once.
+/// Returns the new contents along with the number of ranges that were replaced.
+pub fn replace_ranges<S: AsRef<str>>(
+    contents: &str,
+    replacements: impl IntoIterator<Item = (Range<usize>, S)>,
+) -> (String, usize) {
+    let mut replacements = replacements.into_iter().collect::<Vec<_>>();
+    // replace from the end so that earlier ranges remain valid
+    replacements.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+    replacements.dedup_by_key(|(range, _)| range.start);
+
+    let mut contents = contents.to_string();
+    for (range, new_value) in &replacements {
+        contents.replace_range(range.clone(), new_value.as_ref());
+    }
+    (contents, replacements.len())
+}
+
 #[derive(Clone, Debug)]
 pub struct VersionReplacement {
     pub old_version: String,
@@ -0,0 +1,119 @@
+use rnix::types::{AttrSet, EntryHolder, KeyValue, LetIn, TokenWrapper, TypedNode};
+use rnix::{SyntaxKind, SyntaxNode, TextRange};
+
+use super::{file, replace_ranges, VersionReplacement};
+use crate::config::NixConfig;
+use crate::error::Result;
+use crate::replacers::ReplacementBuilder;
+
+/// Updates the `version` attribute of derivations in Nix files.
+/// A derivation is identified by the `pname` in the same attribute set as the `version`.
+/// If the derivation uses `inherit version;`, the binding it inherits from is updated instead.
+pub struct Replacer {
+    versions: VersionReplacement,
+    config: NixConfig,
+}
+
+impl Replacer {
+    #[must_use]
+    pub fn new(versions: VersionReplacement, config: NixConfig) -> Self {
+        Self { versions, config }
+    }
+}
+
+impl ReplacementBuilder for Replacer {
+    fn determine_replacements(self) -> Result<Option<Vec<file::Replacer>>> {
+        let mut replacers = Vec::new();
+
+        for path in self.config.files {
+            let contents = std::fs::read_to_string(&path)?;
+            let ast = rnix::parse(&contents);
+            if let Some(error) = ast.errors().into_iter().next() {
+                return Err(error.into());
+            }
+
+            let ranges =
+                find_versions(&ast.node(), &self.config.pnames, &self.versions.old_version);
+            if ranges.is_empty() {
+                continue;
+            }
+
+            let new_version = &self.versions.new_version;
+            let (contents, _) = replace_ranges(
+                &contents,
+                ranges.into_iter().map(|range| (range.into(), new_version)),
+            );
+
+            replacers.push(file::Replacer::with_contents(path, contents.as_bytes())?);
+        }
+
+        Ok(Some(replacers))
+    }
+}
+
+/// Finds the text of each `version` string belonging to a derivation with one of the given names.
+fn find_versions(root: &SyntaxNode, pnames: &[String], old_version: &str) -> Vec<TextRange> {
+    root.descendants()
+        .filter_map(AttrSet::cast)
+        .filter(|set| {
+            string_binding(set.entries(), "pname").is_some_and(|(pname, _)| pnames.contains(&pname))
+        })
+        .filter_map(|set| {
+            let (version, range) =
+                string_binding(set.entries(), "version").or_else(|| inherited_version(&set))?;
+            (version == old_version).then_some(range)
+        })
+        .collect()
+}
+
+/// Looks up the `version` binding that the attribute set inherits through `inherit version;`.
+/// The nearest enclosing `let` or recursive attribute set that defines it is used.
+fn inherited_version(set: &AttrSet) -> Option<(String, TextRange)> {
+    let inherits_version = set.inherits().any(|inherit| {
+        inherit.from().is_none() && inherit.idents().any(|ident| ident.as_str() == "version")
+    });
+    if !inherits_version {
+        return None;
+    }
+
+    set.node().ancestors().skip(1).find_map(|node| {
+        if let Some(let_in) = LetIn::cast(node.clone()) {
+            string_binding(let_in.entries(), "version")
+        } else {
+            AttrSet::cast(node)
+                .filter(AttrSet::recursive)
+                .and_then(|set| string_binding(set.entries(), "version"))
+        }
+    })
+}
+
+/// Finds a binding of a plain string to the given name, returning the string's contents and
+/// their location. Strings containing interpolations are ignored.
+fn string_binding(
+    mut entries: Box<dyn Iterator<Item = KeyValue>>,
+    name: &str,
+) -> Option<(String, TextRange)> {
+    let entry = entries.find(|entry| {
+        entry.key().is_some_and(|key| {
+            let mut path = key.path();
+            path.next().is_some_and(|part| part.text() == name) && path.next().is_none()
+        })
+    })?;
+    let value = entry.value()?;
+    if value.kind() != SyntaxKind::NODE_STRING {
+        return None;
+    }
+
+    let mut parts = value.children_with_tokens().filter(|part| {
+        !matches!(
+            part.kind(),
+            SyntaxKind::TOKEN_STRING_START | SyntaxKind::TOKEN_STRING_END
+        )
+    });
+    let content = parts.next()?.into_token()?;
+    if content.kind() != SyntaxKind::TOKEN_STRING_CONTENT || parts.next().is_some() {
+        return None;
+    }
+
+    Some((content.text().to_string(), content.text_range()))
+}
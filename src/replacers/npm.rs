@@ -0,0 +1,190 @@
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use super::{file, json, yaml, VersionReplacement};
+use crate::config::NpmReplaceMode;
+use crate::error::Result;
+use crate::glob;
+use crate::replacers::ReplacementBuilder;
+
+/// Updates the `version` field of package.json files.
+/// Unlike a simple replacement, dependency versions that happen to match the old version are left
+/// untouched. The matching entries of the root package-lock.json are updated as well.
+pub struct Replacer {
+    lock_path: PathBuf,
+    versions: VersionReplacement,
+    replace_mode: NpmReplaceMode,
+}
+
+impl Replacer {
+    #[must_use]
+    pub fn new(versions: VersionReplacement, replace_mode: NpmReplaceMode) -> Self {
+        Self {
+            lock_path: PathBuf::from("package-lock.json"),
+            versions,
+            replace_mode,
+        }
+    }
+}
+
+/// The fields of a package.json that are needed to discover packages
+#[derive(Debug, Deserialize)]
+struct PackageJson {
+    name: Option<String>,
+    workspaces: Option<Workspaces>,
+}
+
+/// npm and yarn accept either a list of patterns or an object containing them
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Workspaces {
+    Patterns(Vec<String>),
+    Config {
+        #[serde(default)]
+        packages: Vec<String>,
+    },
+}
+
+#[derive(Debug)]
+struct Package {
+    /// the directory containing the package.json, relative to the workspace root
+    directory: PathBuf,
+    name: Option<String>,
+}
+
+impl ReplacementBuilder for Replacer {
+    fn determine_replacements(self) -> Result<Option<Vec<file::Replacer>>> {
+        let mut replacers = Vec::new();
+
+        let packages = find_packages()?;
+        let packages = packages.iter().filter(|package| match &self.replace_mode {
+            NpmReplaceMode::Autodetect => true,
+            NpmReplaceMode::Packages(names) => package
+                .name
+                .as_ref()
+                .is_some_and(|name| names.contains(name)),
+        });
+
+        let mut bumped_directories = Vec::new();
+        for package in packages {
+            let path = package.directory.join("package.json");
+            let mut contents = std::fs::read_to_string(&path)?;
+            let replaced = json::replace_string(
+                &mut contents,
+                &["version"],
+                &self.versions.old_version,
+                &self.versions.new_version,
+            );
+            if !replaced {
+                continue;
+            }
+
+            bumped_directories.push(package.directory.clone());
+            replacers.push(file::Replacer::with_contents(path, contents.as_bytes())?);
+        }
+
+        if !bumped_directories.is_empty() && self.lock_path.exists() {
+            let mut contents = std::fs::read_to_string(&self.lock_path)?;
+            json::validate(&contents)?;
+
+            let mut changed = false;
+            for directory in &bumped_directories {
+                let key = lock_key(directory);
+                if key.is_empty() {
+                    changed |= json::replace_string(
+                        &mut contents,
+                        &["version"],
+                        &self.versions.old_version,
+                        &self.versions.new_version,
+                    );
+                }
+                changed |= json::replace_string(
+                    &mut contents,
+                    &["packages", &key, "version"],
+                    &self.versions.old_version,
+                    &self.versions.new_version,
+                );
+            }
+
+            if changed {
+                replacers.push(file::Replacer::with_contents(
+                    self.lock_path.clone(),
+                    contents.as_bytes(),
+                )?);
+            }
+        }
+
+        Ok(Some(replacers))
+    }
+}
+
+/// Returns the root package along with every package in its workspace.
+fn find_packages() -> Result<Vec<Package>> {
+    let root_directory = PathBuf::new();
+    let root = read_package(&root_directory)?;
+
+    let mut patterns = match root.workspaces {
+        Some(Workspaces::Patterns(patterns) | Workspaces::Config { packages: patterns }) => {
+            patterns
+        }
+        None => Vec::new(),
+    };
+    let pnpm_workspace = Path::new("pnpm-workspace.yaml");
+    if pnpm_workspace.exists() {
+        let contents = std::fs::read_to_string(pnpm_workspace)?;
+        patterns.extend(pnpm_patterns(&contents)?);
+    }
+
+    let mut packages = vec![Package {
+        directory: root_directory,
+        name: root.name,
+    }];
+    if patterns.is_empty() {
+        return Ok(packages);
+    }
+
+    for directory in glob::expand(&patterns)? {
+        if !directory.join("package.json").is_file() {
+            continue;
+        }
+        let package = read_package(&directory)?;
+        packages.push(Package {
+            directory,
+            name: package.name,
+        });
+    }
+
+    Ok(packages)
+}
+
+fn read_package(directory: &Path) -> Result<PackageJson> {
+    let contents = std::fs::read_to_string(directory.join("package.json"))?;
+    let package = serde_json::from_str(&contents)?;
+
+    Ok(package)
+}
+
+/// Reads the package patterns from a pnpm-workspace.yaml file.
+fn pnpm_patterns(contents: &str) -> Result<Vec<String>> {
+    let document = yaml::parse(contents)?;
+    let patterns = match document.get("packages") {
+        Some(yaml::Node::Sequence(items)) => items
+            .iter()
+            .filter_map(yaml::Node::as_str)
+            .map(ToString::to_string)
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    Ok(patterns)
+}
+
+/// The key of a workspace package in the `packages` table of package-lock.json.
+/// The root package uses an empty key.
+fn lock_key(directory: &Path) -> String {
+    directory
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
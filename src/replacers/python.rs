@@ -0,0 +1,82 @@
+use std::path::Path;
+
+use super::{file, toml, VersionReplacement};
+use crate::config::PythonConfig;
+use crate::error::{Error, Result};
+use crate::replacers::ReplacementBuilder;
+
+/// The keys of a pyproject.toml that may hold the project's version
+const VERSION_KEYS: [&[&str]; 2] = [&["project", "version"], &["tool", "poetry", "version"]];
+
+/// Updates the version of Python projects.
+/// The version is changed in each pyproject.toml, and optionally in a module's `__version__`.
+/// A project whose version is dynamic (e.g. provided by maturin from Cargo.toml) is left alone.
+pub struct Replacer {
+    versions: VersionReplacement,
+    config: PythonConfig,
+}
+
+impl Replacer {
+    #[must_use]
+    pub fn new(versions: VersionReplacement, config: PythonConfig) -> Self {
+        Self { versions, config }
+    }
+}
+
+impl ReplacementBuilder for Replacer {
+    fn determine_replacements(self) -> Result<Option<Vec<file::Replacer>>> {
+        let mut replacers = Vec::new();
+
+        for path in self.config.pyprojects {
+            let contents = std::fs::read_to_string(&path)?;
+            let mut pyproject = contents.parse::<toml_edit::DocumentMut>()?;
+
+            let mut changed = false;
+            for keys in VERSION_KEYS {
+                changed |= toml::get_mut(pyproject.as_table_mut(), keys)
+                    .is_some_and(|version| toml::replace_version(version, &self.versions));
+            }
+
+            if changed {
+                replacers.push(file::Replacer::with_contents(
+                    path,
+                    pyproject.to_string().as_bytes(),
+                )?);
+            }
+        }
+
+        if let Some(path) = self.config.version_module {
+            let contents = std::fs::read_to_string(&path)?;
+            if let Some(contents) = replace_dunder_version(&contents, &path, &self.versions)? {
+                replacers.push(file::Replacer::with_contents(path, contents.as_bytes())?);
+            }
+        }
+
+        Ok(Some(replacers))
+    }
+}
+
+/// Replaces the value of a module-level `__version__` assignment if it matches the old version.
+fn replace_dunder_version(
+    contents: &str,
+    path: &Path,
+    versions: &VersionReplacement,
+) -> Result<Option<String>> {
+    let regex = regex::Regex::new(
+        r#"(?m)^__version__\s*(?::\s*str\s*)?=\s*(?:"(?P<double>[^"]*)"|'(?P<single>[^']*)')"#,
+    )?;
+    let captures = regex
+        .captures(contents)
+        .ok_or_else(|| Error::VersionAssignmentNotFound(path.to_path_buf()))?;
+    let Some(version) = captures.name("double").or_else(|| captures.name("single")) else {
+        return Ok(None);
+    };
+    if version.as_str() != versions.old_version {
+        return Ok(None);
+    }
+
+    let mut contents = contents.to_string();
+    contents.replace_range(version.range(), &versions.new_version);
+
+    Ok(Some(contents))
+}
@@ -0,0 +1,51 @@
+use std::{fs, path::PathBuf};
+
+use super::{file, ContentReplacer};
+use crate::config::Expect;
+use crate::error::{Error, Result};
+use crate::replacers::ReplacementBuilder;
+
+/// A single rule of a file, along with the number of values it must replace
+pub struct Rule {
+    /// describes the rule in errors
+    pub description: String,
+    pub expect: Expect,
+    pub replacer: Box<dyn ContentReplacer>,
+}
+
+/// Applies several rules to the same file, producing a single replacement.
+/// Each rule edits the contents produced by the previous one, so the changes of one rule can't be
+/// overwritten by another when they are persisted.
+pub struct Replacer {
+    path: PathBuf,
+    rules: Vec<Rule>,
+}
+
+impl Replacer {
+    #[must_use]
+    pub fn new(path: PathBuf, rules: Vec<Rule>) -> Self {
+        Self { path, rules }
+    }
+}
+
+impl ReplacementBuilder for Replacer {
+    fn determine_replacements(self) -> Result<Option<Vec<file::Replacer>>> {
+        let mut contents = fs::read_to_string(&self.path)?;
+        for rule in self.rules {
+            let (new_contents, count) = rule.replacer.replace_contents(&contents)?;
+            if !rule.expect.is_met(count) {
+                return Err(Error::UnexpectedReplacementCount {
+                    path: self.path,
+                    rule: rule.description,
+                    expect: rule.expect,
+                    count,
+                });
+            }
+            contents = new_contents;
+        }
+
+        let replacer = file::Replacer::with_contents(self.path, contents.as_bytes())?;
+
+        Ok(Some(vec![replacer]))
+    }
+}
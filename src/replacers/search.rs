@@ -2,7 +2,7 @@ use memmap::{Mmap, MmapMut};
 use std::ops::Range;
 use std::{fs, fs::File, io::prelude::*, path::PathBuf};
 
-use super::file;
+use super::{file, ContentReplacer};
 use crate::error::{Error, Result};
 use crate::replacers::ReplacementBuilder;
 
@@ -41,7 +41,7 @@ impl Replacer {
     }
 
     /// Gives the positions in the buffer that need to be replaced.
-    fn determine_replacement_locations(&self, source_buf: &Mmap) -> Result<Vec<Range<usize>>> {
+    fn determine_replacement_locations(&self, source_buf: &[u8]) -> Result<Vec<Range<usize>>> {
         // Find all locations in the file with the version string found
         self.regex
             .captures_iter(source_buf)
@@ -135,6 +135,25 @@ impl Replacer {
     }
 }
 
+impl ContentReplacer for Replacer {
+    fn replace_contents(&self, contents: &str) -> Result<(String, usize)> {
+        let source = contents.as_bytes();
+        let replacement_locations = self.determine_replacement_locations(source)?;
+        let count = replacement_locations.len();
+
+        let mut replaced = Vec::with_capacity(source.len());
+        let mut prev_end = 0;
+        for range in replacement_locations {
+            replaced.extend_from_slice(&source[prev_end..range.start]);
+            replaced.extend_from_slice(&self.new_data);
+            prev_end = range.end;
+        }
+        replaced.extend_from_slice(&source[prev_end..]);
+
+        Ok((String::from_utf8(replaced)?, count))
+    }
+}
+
 impl ReplacementBuilder for Replacer {
     fn determine_replacements(self) -> Result<Option<Vec<file::Replacer>>> {
         let mut replacers = Vec::new();
@@ -0,0 +1,152 @@
+use std::{fs, path::PathBuf};
+
+use super::{file, json, toml, yaml, ContentReplacer, VersionReplacement};
+use crate::config::Selector;
+use crate::error::{Error, Result};
+use crate::replacers::ReplacementBuilder;
+
+/// Replaces the version in a single value of a JSON, TOML, or YAML file.
+/// The selector must resolve to exactly one value, and that value must be the old version.
+/// Only the selected value is changed, so the rest of the file stays intact.
+pub struct Replacer {
+    path: PathBuf,
+    selector: Selector,
+    versions: VersionReplacement,
+}
+
+impl Replacer {
+    #[must_use]
+    pub fn new(path: PathBuf, selector: Selector, versions: VersionReplacement) -> Self {
+        Self {
+            path,
+            selector,
+            versions,
+        }
+    }
+
+    fn replace_json(&self, contents: &mut String, pointer: &str) -> Result<()> {
+        json::validate(contents)?;
+
+        let segments = parse_pointer(pointer)?;
+        let segments = segments.iter().map(String::as_str).collect::<Vec<_>>();
+        let range = json::find_value(contents, &segments).ok_or_else(|| self.not_found())?;
+        let current = &contents[range.clone()];
+        match serde_json::from_str::<String>(current) {
+            Ok(current) if current == self.versions.old_version => {}
+            Ok(current) => return Err(self.mismatch(current)),
+            Err(_) => return Err(self.mismatch(current.to_string())),
+        }
+
+        let new_value = serde_json::Value::from(self.versions.new_version.as_str()).to_string();
+        contents.replace_range(range, &new_value);
+        Ok(())
+    }
+
+    fn replace_toml(&self, contents: &mut String, key: &str) -> Result<()> {
+        let keys =
+            toml_edit::Key::parse(key).map_err(|_| Error::InvalidValuePath(key.to_string()))?;
+        let keys = keys.iter().map(toml_edit::Key::get).collect::<Vec<_>>();
+
+        let mut document = contents.parse::<toml_edit::DocumentMut>()?;
+        let item = toml::get_mut(document.as_table_mut(), &keys).ok_or_else(|| self.not_found())?;
+        if !toml::replace_version(item, &self.versions) {
+            let found = match item.as_str() {
+                Some(current) => current.to_string(),
+                None => item.to_string().trim().to_string(),
+            };
+            return Err(self.mismatch(found));
+        }
+
+        *contents = document.to_string();
+        Ok(())
+    }
+
+    fn replace_yaml(&self, contents: &mut String, value_path: &str) -> Result<()> {
+        let value_path = yaml::parse_path(value_path)?;
+        let documents = yaml::parse_documents(contents)?;
+        let nodes = documents
+            .iter()
+            .flat_map(|document| document.select(&value_path))
+            .collect::<Vec<_>>();
+
+        let scalar = match nodes.as_slice() {
+            [] => return Err(self.not_found()),
+            [yaml::Node::Scalar(scalar)] => scalar,
+            [_] => return Err(self.mismatch(String::from("a collection"))),
+            nodes => {
+                return Err(Error::SelectorAmbiguous {
+                    path: self.path.clone(),
+                    selector: self.selector.clone(),
+                    count: nodes.len(),
+                })
+            }
+        };
+
+        let (range, new_value) = scalar
+            .replaceable_range(contents)
+            .zip(yaml::replace_version(&scalar.value, &self.versions))
+            .ok_or_else(|| self.mismatch(scalar.value.clone()))?;
+
+        contents.replace_range(range, &new_value);
+        Ok(())
+    }
+
+    fn not_found(&self) -> Error {
+        Error::SelectorNotFound {
+            path: self.path.clone(),
+            selector: self.selector.clone(),
+        }
+    }
+
+    fn mismatch(&self, found: String) -> Error {
+        Error::SelectorValueMismatch {
+            path: self.path.clone(),
+            selector: self.selector.clone(),
+            expected: self.versions.old_version.clone(),
+            found,
+        }
+    }
+}
+
+impl ReplacementBuilder for Replacer {
+    fn determine_replacements(self) -> Result<Option<Vec<file::Replacer>>> {
+        let contents = fs::read_to_string(&self.path)?;
+        let (contents, _) = self.replace_contents(&contents)?;
+        let replacer = file::Replacer::with_contents(self.path, contents.as_bytes())?;
+
+        Ok(Some(vec![replacer]))
+    }
+}
+
+impl ContentReplacer for Replacer {
+    /// A selector always replaces exactly one value, or fails.
+    fn replace_contents(&self, contents: &str) -> Result<(String, usize)> {
+        let mut contents = contents.to_string();
+        match &self.selector {
+            Selector::Json(pointer) => self.replace_json(&mut contents, pointer)?,
+            Selector::Toml(key) => self.replace_toml(&mut contents, key)?,
+            Selector::Yaml(value_path) => self.replace_yaml(&mut contents, value_path)?,
+        }
+
+        Ok((contents, 1))
+    }
+}
+
+/// Splits a JSON pointer (RFC 6901) into its reference tokens.
+///
+/// # Errors
+///
+/// - the pointer is not empty and doesn't start with `/`
+pub fn parse_pointer(pointer: &str) -> Result<Vec<String>> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let tokens = pointer
+        .strip_prefix('/')
+        .ok_or_else(|| Error::InvalidValuePath(pointer.to_string()))?;
+
+    Ok(tokens
+        .split('/')
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
@@ -1,7 +1,7 @@
 use memmap::{Mmap, MmapMut};
 use std::{fs, fs::File, io::prelude::*, ops::DerefMut, path::PathBuf};
 
-use super::{file, ReplacementBuilder};
+use super::{file, ContentReplacer, ReplacementBuilder};
 use crate::error::{Error, Result};
 
 pub struct Replacer {
@@ -64,6 +64,15 @@ impl ReplacementBuilder for Replacer {
     }
 }
 
+impl ContentReplacer for Replacer {
+    fn replace_contents(&self, contents: &str) -> Result<(String, usize)> {
+        let count = self.regex.find_iter(contents.as_bytes()).count();
+        let replaced = replace(&self.regex, contents.as_bytes(), &self.new_data);
+
+        Ok((String::from_utf8(replaced.into_owned())?, count))
+    }
+}
+
 fn replace<'a>(
     regex: &regex::bytes::Regex,
     buf: &'a [u8],
@@ -0,0 +1,37 @@
+use super::VersionReplacement;
+
+/// Finds the item at the given path of keys.
+/// Unlike indexing into a `toml_edit::Item`, this never inserts missing tables.
+pub fn get_mut<'a>(
+    table: &'a mut dyn toml_edit::TableLike,
+    path: &[&str],
+) -> Option<&'a mut toml_edit::Item> {
+    let (key, rest) = path.split_first()?;
+    let item = table.get_mut(key)?;
+    if rest.is_empty() {
+        Some(item)
+    } else {
+        get_mut(item.as_table_like_mut()?, rest)
+    }
+}
+
+/// Replaces a version string in place if it matches the old version.
+/// The surrounding whitespace and comments of the value are kept intact.
+pub fn replace_version(item: &mut toml_edit::Item, versions: &VersionReplacement) -> bool {
+    let Some(value) = item.as_value_mut() else {
+        return false;
+    };
+    if value.as_str() != Some(versions.old_version.as_str()) {
+        return false;
+    }
+
+    set_string(value, &versions.new_version);
+    true
+}
+
+/// Changes the value to the given string, keeping its surrounding whitespace and comments.
+pub fn set_string(value: &mut toml_edit::Value, new: &str) {
+    let decor = value.decor().clone();
+    *value = toml_edit::Value::from(new);
+    *value.decor_mut() = decor;
+}
@@ -0,0 +1,365 @@
+use anyhow::anyhow;
+use saphyr_parser::{Event, Parser, ScalarStyle, Span, StrInput};
+use std::{fs, ops::Range, path::PathBuf};
+
+use super::{file, replace_ranges, ContentReplacer, VersionReplacement};
+use crate::error::{Error, Result};
+use crate::replacers::ReplacementBuilder;
+
+/// Replaces the version in the values at the given paths of a YAML file.
+/// Only the targeted values are changed, so comments and formatting are preserved.
+///
+/// A value is replaced if it is equal to the old version, or if it is a container image reference
+/// whose tag is the old version (e.g. `registry/app:0.1.0`).
+/// Every document of a multi-document file is searched.
+pub struct Replacer {
+    path: PathBuf,
+    value_paths: Vec<Vec<PathSegment>>,
+    versions: VersionReplacement,
+}
+
+impl Replacer {
+    /// # Errors
+    ///
+    /// - one of the `value_paths` is not a valid path
+    pub fn new(
+        path: PathBuf,
+        old_content: &str,
+        value_paths: &[String],
+        new_content: &str,
+    ) -> Result<Self> {
+        let value_paths = value_paths
+            .iter()
+            .map(|value_path| parse_path(value_path))
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            path,
+            value_paths,
+            versions: VersionReplacement {
+                old_version: old_content.to_string(),
+                new_version: new_content.to_string(),
+            },
+        })
+    }
+}
+
+impl ReplacementBuilder for Replacer {
+    fn determine_replacements(self) -> Result<Option<Vec<file::Replacer>>> {
+        let contents = fs::read_to_string(&self.path)?;
+        let (contents, count) = self.replace_contents(&contents)?;
+        if count == 0 {
+            return Err(Error::InvalidReplacementCount(0));
+        }
+        let replacer = file::Replacer::with_contents(self.path, contents.as_bytes())?;
+
+        Ok(Some(vec![replacer]))
+    }
+}
+
+impl ContentReplacer for Replacer {
+    fn replace_contents(&self, contents: &str) -> Result<(String, usize)> {
+        let documents = parse_documents(contents)?;
+
+        let mut replacements = Vec::new();
+        for document in &documents {
+            for value_path in &self.value_paths {
+                for node in document.select(value_path) {
+                    let Node::Scalar(scalar) = node else {
+                        continue;
+                    };
+                    let Some(new_value) = replace_version(&scalar.value, &self.versions) else {
+                        continue;
+                    };
+                    if let Some(range) = scalar.replaceable_range(contents) {
+                        replacements.push((range, new_value));
+                    }
+                }
+            }
+        }
+
+        Ok(replace_ranges(contents, replacements))
+    }
+}
+
+/// Determines the new value of a scalar, if it contains the old version.
+#[must_use]
+pub fn replace_version(value: &str, versions: &VersionReplacement) -> Option<String> {
+    if value == versions.old_version {
+        return Some(versions.new_version.clone());
+    }
+
+    // container image references are of the form `name[:tag][@digest]`
+    let (reference, digest) = match value.split_once('@') {
+        Some((reference, digest)) => (reference, Some(digest)),
+        None => (value, None),
+    };
+    let (name, tag) = reference.rsplit_once(':')?;
+    if tag != versions.old_version {
+        return None;
+    }
+
+    let mut new_value = format!("{name}:{}", versions.new_version);
+    if let Some(digest) = digest {
+        new_value.push('@');
+        new_value.push_str(digest);
+    }
+    Some(new_value)
+}
+
+/// A node of a parsed YAML document.
+/// Aliases are not resolved, and tags are ignored.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Node {
+    Scalar(Scalar),
+    Sequence(Vec<Node>),
+    Mapping(Vec<(Node, Node)>),
+    Alias,
+}
+
+/// A scalar value along with its location in the source document
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Scalar {
+    pub value: String,
+    /// the byte range of the scalar in the source, including any quotes
+    pub span: Range<usize>,
+}
+
+impl Scalar {
+    /// The byte range of the scalar's contents, excluding any quotes
+    #[must_use]
+    pub fn content_range(&self, source: &str) -> Range<usize> {
+        let text = &source[self.span.clone()];
+        if text.starts_with(['"', '\'']) {
+            self.span.start + 1..self.span.end - 1
+        } else {
+            self.span.clone()
+        }
+    }
+
+    /// The byte range of the scalar's contents, if the value can be replaced in place.
+    /// Values with escapes or line folding differ from their source text, so they can't be.
+    #[must_use]
+    pub fn replaceable_range(&self, source: &str) -> Option<Range<usize>> {
+        let range = self.content_range(source);
+        (source[range.clone()] == self.value).then_some(range)
+    }
+}
+
+/// Finds the end of a quoted scalar starting at `start`, including the closing quote.
+/// The parser's span of a quoted scalar may extend past the quote to the end of the line.
+fn quoted_end(source: &str, start: usize, quote: u8) -> Option<usize> {
+    let bytes = source.as_bytes();
+    let mut position = start + 1;
+    while position < bytes.len() {
+        match bytes[position] {
+            b'\\' if quote == b'"' => position += 2,
+            // single quotes are escaped by doubling them
+            b'\'' if quote == b'\'' && bytes.get(position + 1) == Some(&b'\'') => position += 2,
+            byte if byte == quote => return Some(position + 1),
+            _ => position += 1,
+        }
+    }
+
+    None
+}
+
+/// A segment of a path to a value in a YAML document
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathSegment {
+    /// a key of a mapping
+    Key(String),
+    /// an index of a sequence
+    Index(usize),
+    /// every item of a sequence, written as `[*]`
+    Wildcard,
+}
+
+impl Node {
+    /// Returns the value of the given key if this node is a mapping.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&Node> {
+        match self {
+            Node::Mapping(entries) => entries
+                .iter()
+                .find(|(entry_key, _)| entry_key.as_str() == Some(key))
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Node::Scalar(scalar) => Some(&scalar.value),
+            _ => None,
+        }
+    }
+
+    /// Returns all nodes found at the given path.
+    #[must_use]
+    pub fn select(&self, path: &[PathSegment]) -> Vec<&Node> {
+        let Some((segment, rest)) = path.split_first() else {
+            return vec![self];
+        };
+
+        match (segment, self) {
+            (PathSegment::Key(key), Node::Mapping(_)) => self
+                .get(key)
+                .map(|node| node.select(rest))
+                .unwrap_or_default(),
+            (PathSegment::Index(index), Node::Sequence(items)) => items
+                .get(*index)
+                .map(|node| node.select(rest))
+                .unwrap_or_default(),
+            (PathSegment::Wildcard, Node::Sequence(items)) => {
+                items.iter().flat_map(|node| node.select(rest)).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Parses a path such as `spec.containers[*].image` or `items[0].version`.
+///
+/// # Errors
+///
+/// - the path is empty or contains an invalid index
+pub fn parse_path(path: &str) -> Result<Vec<PathSegment>> {
+    let invalid_path = || Error::InvalidValuePath(path.to_string());
+
+    let mut segments = Vec::new();
+    for part in path.split('.') {
+        let (key, mut indices) = match part.find('[') {
+            Some(start) => part.split_at(start),
+            None => (part, ""),
+        };
+        if !key.is_empty() {
+            segments.push(PathSegment::Key(key.to_string()));
+        }
+
+        while !indices.is_empty() {
+            let (index, rest) = indices
+                .strip_prefix('[')
+                .and_then(|indices| indices.split_once(']'))
+                .ok_or_else(invalid_path)?;
+            let segment = match index {
+                "*" => PathSegment::Wildcard,
+                index => PathSegment::Index(index.parse().map_err(|_| invalid_path())?),
+            };
+            segments.push(segment);
+            indices = rest;
+        }
+
+        if part.is_empty() {
+            return Err(invalid_path());
+        }
+    }
+
+    Ok(segments)
+}
+
+/// Parses the first document of the YAML source.
+/// An empty document is parsed as an empty scalar.
+///
+/// # Errors
+///
+/// - the source is not valid YAML
+pub fn parse(source: &str) -> Result<Node> {
+    let document = parse_documents(source)?.into_iter().next();
+
+    Ok(document.unwrap_or(Node::Scalar(Scalar {
+        value: String::new(),
+        span: 0..0,
+    })))
+}
+
+/// Parses every document of the YAML source.
+///
+/// # Errors
+///
+/// - the source is not valid YAML
+pub fn parse_documents(source: &str) -> Result<Vec<Node>> {
+    let mut builder = Builder {
+        source,
+        parser: Parser::new_from_str(source),
+        // the parser reports positions in chars, which need to be converted to bytes
+        offsets: source
+            .char_indices()
+            .map(|(offset, _)| offset)
+            .chain(std::iter::once(source.len()))
+            .collect(),
+    };
+
+    let mut documents = Vec::new();
+    loop {
+        match builder.next_event()? {
+            (Event::StreamEnd, _) => return Ok(documents),
+            (Event::StreamStart | Event::DocumentStart(_) | Event::DocumentEnd, _) => {}
+            (event, span) => documents.push(builder.build_node(event, span)?),
+        }
+    }
+}
+
+struct Builder<'a> {
+    source: &'a str,
+    parser: Parser<'a, StrInput<'a>>,
+    offsets: Vec<usize>,
+}
+
+impl<'a> Builder<'a> {
+    fn build_node(&mut self, event: Event<'a>, span: Span) -> Result<Node> {
+        match event {
+            Event::Scalar(value, style, ..) => {
+                let start = self.offsets[span.start.index()];
+                let mut end = self.offsets[span.end.index()];
+                let quote = match style {
+                    ScalarStyle::SingleQuoted => Some(b'\''),
+                    ScalarStyle::DoubleQuoted => Some(b'"'),
+                    _ => None,
+                };
+                if let Some(quote) = quote {
+                    end = quoted_end(self.source, start, quote).unwrap_or(end);
+                }
+
+                Ok(Node::Scalar(Scalar {
+                    value: value.into_owned(),
+                    span: start..end,
+                }))
+            }
+            Event::SequenceStart(..) => {
+                let mut items = Vec::new();
+                loop {
+                    match self.next_event()? {
+                        (Event::SequenceEnd, _) => break,
+                        (event, span) => items.push(self.build_node(event, span)?),
+                    }
+                }
+                Ok(Node::Sequence(items))
+            }
+            Event::MappingStart(..) => {
+                let mut entries = Vec::new();
+                loop {
+                    let key = match self.next_event()? {
+                        (Event::MappingEnd, _) => break,
+                        (event, span) => self.build_node(event, span)?,
+                    };
+                    let (event, span) = self.next_event()?;
+                    let value = self.build_node(event, span)?;
+                    entries.push((key, value));
+                }
+                Ok(Node::Mapping(entries))
+            }
+            _ => Ok(Node::Alias),
+        }
+    }
+
+    fn next_event(&mut self) -> Result<(Event<'a>, Span)> {
+        let event = self
+            .parser
+            .next_event()
+            .ok_or_else(|| Error::Other(anyhow!("unexpected end of yaml document")))??;
+
+        Ok(event)
+    }
+}
@@ -1,11 +1,30 @@
 mod file;
-use std::{io::Read, path::Path};
+use std::{
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
 
 use file::Jail;
 
 use crate::{
-    config::{CargoReplaceMode, Config, FileTableData},
-    replacers::{cargo, search, ReplacementBuilder, VersionReplacement},
+    changelog::{display_commit_type, generate_changelog_entry},
+    config::{
+        BuildMetadataConfig, BuildMetadataPart, CargoReplaceMode, ChangelogConfig, CommitMatcher,
+        CommitPolicy, Config, Expect, FileRules, FileTableData, Increment, IncrementRules,
+        MergeCommits, NixConfig, NonConventionalCommits, NpmReplaceMode, PythonConfig,
+        ReferencePattern, RegexReplace, Selector, TagSelection, UnlistedCommits,
+    },
+    error::Error,
+    glob,
+    replacers::{
+        capture, cargo, nix, npm, python, rules, search, selector, simple, yaml, ContentReplacer,
+        ReplacementBuilder, VersionReplacement,
+    },
+    versioning::{
+        build_metadata, calver, create_tag, determine_increment, get_commits_between_tags,
+        get_commits_since_initial_commit, get_commits_since_tag, get_latest_tag, increment_version,
+        Tag, TagTemplate, VersionIncrement, VersionScheme,
+    },
 };
 
 #[test]
@@ -22,7 +41,9 @@ fn config_simple() {
         )?;
         let config = Config::from_ron(&String::from("bomp.ron"))?;
         let by_file = config.by_file.unwrap();
-        let info: Option<&FileTableData> = by_file.get(Path::new("Cargo.toml"));
+        let info: Option<&FileTableData> = by_file
+            .get(Path::new("Cargo.toml"))
+            .and_then(|rules| rules.rules().first());
 
         if let Some(info) = info {
             assert_eq!(info.search_value, None);
@@ -49,7 +70,9 @@ fn config_search() {
 
         let config = Config::from_ron(&String::from("bomp.ron"))?;
         let by_file = config.by_file.unwrap();
-        let info: Option<&FileTableData> = by_file.get(Path::new("Cargo.toml"));
+        let info: Option<&FileTableData> = by_file
+            .get(Path::new("Cargo.toml"))
+            .and_then(|rules| rules.rules().first());
 
         if let Some(info) = info {
             assert_eq!(info.search_value, Some(String::from("bomper")));
@@ -171,10 +194,11 @@ package3 = "0.1.0"
 "#,
         )?;
 
-        let expected_toml = r#"[package]
+        let expected_toml = r#"
+[package]
 name = "package1"
-version = "0.2.0"
 edition = "2018"
+version = "0.2.0"
 
 [dependencies]
 package2 = "0.1.0"
@@ -390,3 +414,2637 @@ version = "0.1.0"
         Ok(())
     });
 }
+
+/// The cargo replacer should only touch the version values, leaving comments, key order, and
+/// formatting of the rest of the manifest untouched.
+#[test]
+fn cargo_preserve_formatting() {
+    Jail::expect_with(|jail| {
+        jail.create_file(
+            "Cargo.toml",
+            r#"# the workspace root is also a package
+[package]
+name = "package1"
+edition = "2021"
+version   =   "0.1.0" # bumped by bomper
+description = "a package"
+
+[workspace]
+members = [
+    "package2", # keep me
+]
+
+[workspace.package]
+# shared version
+version = "0.1.0"
+authors = ["someone"]
+
+[dependencies]
+package2 = { path = "package2" }
+serde = "0.1.0"
+"#,
+        )?;
+
+        let expected_root = r#"# the workspace root is also a package
+[package]
+name = "package1"
+edition = "2021"
+version   =   "0.2.0" # bumped by bomper
+description = "a package"
+
+[workspace]
+members = [
+    "package2", # keep me
+]
+
+[workspace.package]
+# shared version
+version = "0.2.0"
+authors = ["someone"]
+
+[dependencies]
+package2 = { path = "package2" }
+serde = "0.1.0"
+"#;
+
+        jail.create_file("src/main.rs", "fn main() {}")?;
+
+        jail.create_file(
+            "package2/Cargo.toml",
+            r#"[package]
+# inherited from the workspace
+version.workspace = true
+name = "package2"
+edition = "2021"
+"#,
+        )?;
+
+        jail.create_file("package2/src/lib.rs", "")?;
+
+        jail.create_file(
+            "Cargo.lock",
+            r#"# This file is automatically @generated by Cargo.
+# It is not intended for manual editing.
+version = 3
+
+[[package]]
+name = "package1"
+version = "0.1.0"
+dependencies = [
+ "package2",
+]
+
+[[package]]
+name = "package2"
+version = "0.1.0"
+"#,
+        )?;
+
+        let version_replacement = VersionReplacement {
+            old_version: "0.1.0".to_string(),
+            new_version: "0.2.0".to_string(),
+        };
+
+        let replacers = cargo::Replacer::new(version_replacement, CargoReplaceMode::Autodetect)
+            .determine_replacements()?
+            .expect("replacers should be generated");
+
+        let mut replaced_files = Vec::new();
+        for replacer in replacers {
+            let path = jail.strip_path(&replacer.path)?;
+            if path == "Cargo.toml" {
+                let file_contents = std::fs::read_to_string(replacer.temp_file.path())?;
+                assert_eq!(file_contents, expected_root);
+            }
+            replaced_files.push(path);
+        }
+
+        // package2 inherits its version, so its manifest must not be rewritten
+        replaced_files.sort();
+        assert_eq!(replaced_files, ["Cargo.lock", "Cargo.toml"]);
+
+        Ok(())
+    });
+}
+
+/// Packages that depend on a bumped package through a path dependency need their version
+/// requirement updated as well, otherwise the workspace can't be published.
+#[test]
+#[allow(clippy::too_many_lines)]
+fn cargo_dependency_requirements() {
+    Jail::expect_with(|jail| {
+        jail.create_file(
+            "Cargo.toml",
+            r#"[workspace]
+members = ["package1", "package2", "package3"]
+
+[workspace.dependencies]
+package2 = { path = "package2", version = "0.1.0" }
+"#,
+        )?;
+
+        jail.create_file(
+            "package1/Cargo.toml",
+            r#"[package]
+name = "package1"
+edition = "2021"
+version = "1.0.0"
+
+[dependencies]
+package2 = { workspace = true }
+renamed = { package = "package3", path = "../package3", version = "=0.1.0" }
+serde = { version = "0.1.0", optional = true }
+
+[dev-dependencies.package3]
+path = "../package3"
+version = "~0.1.0"
+
+[target.'cfg(unix)'.build-dependencies]
+package3 = { path = "../package3", version = "^0.1.0" } # keep me
+"#,
+        )?;
+
+        let expected_package1 = r#"[package]
+name = "package1"
+edition = "2021"
+version = "1.0.0"
+
+[dependencies]
+package2 = { workspace = true }
+renamed = { package = "package3", path = "../package3", version = "=0.2.0" }
+serde = { version = "0.1.0", optional = true }
+
+[dev-dependencies.package3]
+path = "../package3"
+version = "~0.2.0"
+
+[target.'cfg(unix)'.build-dependencies]
+package3 = { path = "../package3", version = "^0.2.0" } # keep me
+"#;
+
+        jail.create_file("package1/src/lib.rs", "")?;
+
+        jail.create_file(
+            "package2/Cargo.toml",
+            r#"[package]
+name = "package2"
+edition = "2021"
+version = "0.1.0"
+"#,
+        )?;
+
+        jail.create_file("package2/src/lib.rs", "")?;
+
+        jail.create_file(
+            "package3/Cargo.toml",
+            r#"[package]
+name = "package3"
+edition = "2021"
+version = "0.1.0"
+"#,
+        )?;
+
+        jail.create_file("package3/src/lib.rs", "")?;
+
+        jail.create_file(
+            "Cargo.lock",
+            r#"# This file is automatically @generated by Cargo.
+# It is not intended for manual editing.
+version = 3
+
+[[package]]
+name = "package1"
+version = "1.0.0"
+dependencies = [
+ "package2",
+ "package3",
+]
+
+[[package]]
+name = "package2"
+version = "0.1.0"
+
+[[package]]
+name = "package3"
+version = "0.1.0"
+"#,
+        )?;
+
+        let version_replacement = VersionReplacement {
+            old_version: "0.1.0".to_string(),
+            new_version: "0.2.0".to_string(),
+        };
+
+        let replacers = cargo::Replacer::new(
+            version_replacement,
+            CargoReplaceMode::Packages(vec!["package2".to_string(), "package3".to_string()]),
+        )
+        .determine_replacements()?
+        .expect("replacers should be generated");
+
+        let mut replaced_files = Vec::new();
+        for replacer in replacers {
+            let path = jail.strip_path(&replacer.path)?;
+            let file_contents = std::fs::read_to_string(replacer.temp_file.path())?;
+            match path.as_str() {
+                "Cargo.toml" => assert_eq!(
+                    file_contents,
+                    r#"[workspace]
+members = ["package1", "package2", "package3"]
+
+[workspace.dependencies]
+package2 = { path = "package2", version = "0.2.0" }
+"#
+                ),
+                "package1/Cargo.toml" => assert_eq!(file_contents, expected_package1),
+                _ => {}
+            }
+            replaced_files.push(path);
+        }
+
+        replaced_files.sort();
+        assert_eq!(
+            replaced_files,
+            [
+                "Cargo.lock",
+                "Cargo.toml",
+                "package1/Cargo.toml",
+                "package2/Cargo.toml",
+                "package3/Cargo.toml"
+            ]
+        );
+
+        Ok(())
+    });
+}
+
+/// Bumps a single package of a workspace to its own version, updating the requirements of the
+/// packages that depend on it.
+#[test]
+#[allow(clippy::too_many_lines)]
+fn cargo_independent_packages() {
+    Jail::expect_with(|jail| {
+        jail.create_file(
+            "Cargo.toml",
+            r#"[workspace]
+members = ["package1", "package2"]
+"#,
+        )?;
+        jail.create_file(
+            "package1/Cargo.toml",
+            r#"[package]
+name = "package1"
+version = "1.4.0"
+edition = "2021"
+
+[dependencies]
+package2 = { path = "../package2", version = "0.3.0" }
+"#,
+        )?;
+        jail.create_file("package1/src/main.rs", "fn main() {}")?;
+        jail.create_file(
+            "package2/Cargo.toml",
+            r#"[package]
+name = "package2"
+version = "0.3.0"
+edition = "2021"
+"#,
+        )?;
+        jail.create_file("package2/src/lib.rs", "")?;
+        jail.create_file(
+            "Cargo.lock",
+            r#"# This file is automatically @generated by Cargo.
+# It is not intended for manual editing.
+version = 3
+
+[[package]]
+name = "package1"
+version = "1.4.0"
+dependencies = [
+ "package2",
+]
+
+[[package]]
+name = "package2"
+version = "0.3.0"
+"#,
+        )?;
+
+        let mut packages = cargo::workspace_packages()?
+            .into_iter()
+            .map(|package| (package.name, package.version.to_string(), package.directory))
+            .collect::<Vec<_>>();
+        packages.sort();
+        assert_eq!(
+            packages,
+            [
+                (
+                    "package1".to_string(),
+                    "1.4.0".to_string(),
+                    "package1".into()
+                ),
+                (
+                    "package2".to_string(),
+                    "0.3.0".to_string(),
+                    "package2".into()
+                ),
+            ]
+        );
+
+        let replacers = cargo::Replacer::independent(vec![cargo::PackageBump {
+            name: "package2".to_string(),
+            versions: VersionReplacement {
+                old_version: "0.3.0".to_string(),
+                new_version: "0.4.0".to_string(),
+            },
+        }])
+        .determine_replacements()?
+        .expect("replacers should be generated");
+
+        let mut replaced_files = Vec::new();
+        for replacer in replacers {
+            let path = jail.strip_path(&replacer.path)?;
+            let file_contents = std::fs::read_to_string(replacer.temp_file.path())?;
+            match path.as_str() {
+                // only the requirement changes, the package keeps its version
+                "package1/Cargo.toml" => assert_eq!(
+                    file_contents,
+                    r#"[package]
+name = "package1"
+version = "1.4.0"
+edition = "2021"
+
+[dependencies]
+package2 = { path = "../package2", version = "0.4.0" }
+"#
+                ),
+                "package2/Cargo.toml" => assert!(file_contents.contains(r#"version = "0.4.0""#)),
+                "Cargo.lock" => {
+                    assert!(file_contents.contains("name = \"package1\"\nversion = \"1.4.0\""));
+                    assert!(file_contents.contains("name = \"package2\"\nversion = \"0.4.0\""));
+                }
+                _ => {}
+            }
+            replaced_files.push(path);
+        }
+
+        replaced_files.sort();
+        assert_eq!(
+            replaced_files,
+            ["Cargo.lock", "package1/Cargo.toml", "package2/Cargo.toml"]
+        );
+
+        // the packages don't share a version to move between
+        let result = cargo::Replacer::new(
+            VersionReplacement {
+                old_version: "1.4.0".to_string(),
+                new_version: "1.5.0".to_string(),
+            },
+            CargoReplaceMode::Independent,
+        )
+        .determine_replacements();
+        assert!(matches!(result, Err(Error::SharedIndependentVersion)));
+
+        // an inherited version can't be bumped on its own
+        jail.create_file(
+            "package2/Cargo.toml",
+            r#"[package]
+name = "package2"
+version = { workspace = true }
+edition = "2021"
+"#,
+        )?;
+        jail.create_file(
+            "Cargo.toml",
+            r#"[workspace]
+members = ["package1", "package2"]
+
+[workspace.package]
+version = "0.3.0"
+"#,
+        )?;
+        let result = cargo::Replacer::independent(vec![cargo::PackageBump {
+            name: "package2".to_string(),
+            versions: VersionReplacement {
+                old_version: "0.3.0".to_string(),
+                new_version: "0.4.0".to_string(),
+            },
+        }])
+        .determine_replacements();
+        assert!(matches!(result, Err(Error::InheritedCargoVersion(_))));
+
+        Ok(())
+    });
+}
+
+/// Updates the packages of an npm workspace, leaving dependency versions that happen to match the
+/// old version untouched.
+#[test]
+#[allow(clippy::too_many_lines)]
+fn npm_workspace() {
+    Jail::expect_with(|jail| {
+        jail.create_file(
+            "package.json",
+            r#"{
+    "name": "root",
+    "version": "0.1.0",
+    "private": true,
+    "workspaces": ["packages/*"],
+    "devDependencies": {
+        "typescript": "0.1.0"
+    }
+}
+"#,
+        )?;
+
+        jail.create_file(
+            "packages/a/package.json",
+            r#"{
+  "name": "a",
+  "version": "0.1.0",
+  "dependencies": { "b": "0.1.0", "left-pad": "0.1.0" }
+}
+"#,
+        )?;
+
+        jail.create_file(
+            "packages/b/package.json",
+            r#"{"name":"b","version":"0.1.0"}"#,
+        )?;
+
+        // ignored directories are not searched for packages
+        jail.create_file(".gitignore", "node_modules\n")?;
+        jail.create_file(
+            "packages/a/node_modules/c/package.json",
+            r#"{"name":"c","version":"0.1.0"}"#,
+        )?;
+
+        jail.create_file(
+            "package-lock.json",
+            r#"{
+  "name": "root",
+  "version": "0.1.0",
+  "lockfileVersion": 3,
+  "requires": true,
+  "packages": {
+    "": {
+      "name": "root",
+      "version": "0.1.0",
+      "workspaces": ["packages/*"]
+    },
+    "node_modules/a": { "resolved": "packages/a", "link": true },
+    "node_modules/left-pad": { "version": "0.1.0" },
+    "packages/a": {
+      "version": "0.1.0",
+      "dependencies": { "b": "0.1.0" }
+    },
+    "packages/b": {
+      "version": "0.1.0"
+    }
+  }
+}
+"#,
+        )?;
+
+        let version_replacement = VersionReplacement {
+            old_version: "0.1.0".to_string(),
+            new_version: "0.2.0".to_string(),
+        };
+
+        let replacers = npm::Replacer::new(version_replacement, NpmReplaceMode::Autodetect)
+            .determine_replacements()?
+            .expect("replacers should be generated");
+
+        let mut replaced_files = Vec::new();
+        for replacer in replacers {
+            let path = jail.strip_path(&replacer.path)?;
+            let file_contents = std::fs::read_to_string(replacer.temp_file.path())?;
+            let expected = match path.as_str() {
+                "package.json" => {
+                    r#"{
+    "name": "root",
+    "version": "0.2.0",
+    "private": true,
+    "workspaces": ["packages/*"],
+    "devDependencies": {
+        "typescript": "0.1.0"
+    }
+}
+"#
+                }
+                "packages/a/package.json" => {
+                    r#"{
+  "name": "a",
+  "version": "0.2.0",
+  "dependencies": { "b": "0.1.0", "left-pad": "0.1.0" }
+}
+"#
+                }
+                "packages/b/package.json" => r#"{"name":"b","version":"0.2.0"}"#,
+                "package-lock.json" => {
+                    r#"{
+  "name": "root",
+  "version": "0.2.0",
+  "lockfileVersion": 3,
+  "requires": true,
+  "packages": {
+    "": {
+      "name": "root",
+      "version": "0.2.0",
+      "workspaces": ["packages/*"]
+    },
+    "node_modules/a": { "resolved": "packages/a", "link": true },
+    "node_modules/left-pad": { "version": "0.1.0" },
+    "packages/a": {
+      "version": "0.2.0",
+      "dependencies": { "b": "0.1.0" }
+    },
+    "packages/b": {
+      "version": "0.2.0"
+    }
+  }
+}
+"#
+                }
+                path => panic!("Unexpected file path: {path}"),
+            };
+            assert_eq!(file_contents, expected);
+            replaced_files.push(path);
+        }
+
+        replaced_files.sort();
+        assert_eq!(
+            replaced_files,
+            [
+                "package-lock.json",
+                "package.json",
+                "packages/a/package.json",
+                "packages/b/package.json"
+            ]
+        );
+
+        Ok(())
+    });
+}
+
+/// Only the selected packages of a pnpm workspace are updated
+#[test]
+fn npm_pnpm_packages() {
+    Jail::expect_with(|jail| {
+        jail.create_file(
+            "package.json",
+            r#"{ "name": "root", "version": "0.1.0", "private": true }"#,
+        )?;
+
+        jail.create_file(
+            "pnpm-workspace.yaml",
+            "packages:
+  # all packages in direct subdirs of packages/
+  - 'packages/*'
+  - '!packages/excluded'
+",
+        )?;
+
+        jail.create_file(
+            "packages/a/package.json",
+            r#"{ "name": "a", "version": "0.1.0" }"#,
+        )?;
+        jail.create_file(
+            "packages/b/package.json",
+            r#"{ "name": "b", "version": "0.1.0" }"#,
+        )?;
+        jail.create_file(
+            "packages/excluded/package.json",
+            r#"{ "name": "excluded", "version": "0.1.0" }"#,
+        )?;
+
+        let version_replacement = VersionReplacement {
+            old_version: "0.1.0".to_string(),
+            new_version: "0.2.0".to_string(),
+        };
+
+        let replacers = npm::Replacer::new(
+            version_replacement,
+            NpmReplaceMode::Packages(vec!["a".to_string(), "excluded".to_string()]),
+        )
+        .determine_replacements()?
+        .expect("replacers should be generated");
+
+        let replaced_files = replacers
+            .iter()
+            .map(|replacer| jail.strip_path(&replacer.path))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(replaced_files, ["packages/a/package.json"]);
+
+        let file_contents = std::fs::read_to_string(replacers[0].temp_file.path())?;
+        assert_eq!(file_contents, r#"{ "name": "a", "version": "0.2.0" }"#);
+
+        Ok(())
+    });
+}
+
+/// Updates both the pyproject.toml and the `__version__` of a mixed Rust and Python project
+#[test]
+fn python_project() {
+    Jail::expect_with(|jail| {
+        jail.create_file(
+            "bomp.ron",
+            r#"(
+                python: Some((
+                    version_module: Some("python/package/__init__.py"),
+                )),
+            )
+        "#,
+        )?;
+
+        jail.create_file(
+            "pyproject.toml",
+            r#"[project]
+name = "package"
+version = "0.1.0" # keep me
+dependencies = ["other==0.1.0"]
+
+[tool.poetry]
+version = "0.1.0"
+"#,
+        )?;
+
+        jail.create_file(
+            "python/package/__init__.py",
+            r#"from .package import *
+
+__version__: str = '0.1.0'
+OTHER_VERSION = "0.1.0"
+"#,
+        )?;
+
+        let config = Config::from_ron(&String::from("bomp.ron"))?;
+        let python_config = config.python.expect("python config should be set");
+        assert_eq!(
+            python_config,
+            PythonConfig {
+                version_module: Some("python/package/__init__.py".into()),
+                ..PythonConfig::default()
+            }
+        );
+
+        let version_replacement = VersionReplacement {
+            old_version: "0.1.0".to_string(),
+            new_version: "0.2.0".to_string(),
+        };
+
+        let replacers = python::Replacer::new(version_replacement, python_config)
+            .determine_replacements()?
+            .expect("replacers should be generated");
+
+        let mut replaced_files = Vec::new();
+        for replacer in replacers {
+            let path = jail.strip_path(&replacer.path)?;
+            let file_contents = std::fs::read_to_string(replacer.temp_file.path())?;
+            let expected = match path.as_str() {
+                "pyproject.toml" => {
+                    r#"[project]
+name = "package"
+version = "0.2.0" # keep me
+dependencies = ["other==0.1.0"]
+
+[tool.poetry]
+version = "0.2.0"
+"#
+                }
+                "python/package/__init__.py" => {
+                    r#"from .package import *
+
+__version__: str = '0.2.0'
+OTHER_VERSION = "0.1.0"
+"#
+                }
+                path => panic!("Unexpected file path: {path}"),
+            };
+            assert_eq!(file_contents, expected);
+            replaced_files.push(path);
+        }
+
+        assert_eq!(
+            replaced_files,
+            ["pyproject.toml", "python/package/__init__.py"]
+        );
+
+        Ok(())
+    });
+}
+
+/// Only the versions of the configured derivations are updated, including versions that are
+/// inherited from a `let` binding. Versions of other derivations are left alone.
+#[test]
+fn nix_derivations() {
+    Jail::expect_with(|jail| {
+        jail.create_file(
+            "default.nix",
+            r#"{ pkgs ? import <nixpkgs> {} }:
+let
+  version = "0.1.0";
+in {
+  package1 = pkgs.stdenv.mkDerivation {
+    pname = "package1";
+    inherit version;
+    src = pkgs.fetchurl {
+      pname = "dependency";
+      version = "0.1.0";
+      url = "https://example.com/dependency-0.1.0.tar.gz";
+      hash = "sha256-AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=";
+    };
+  };
+
+  package2 = pkgs.rustPlatform.buildRustPackage {
+    pname = "package2";
+    version = "0.1.0"; # keep me
+    src = ./.;
+  };
+
+  other = pkgs.stdenv.mkDerivation {
+    pname = "other";
+    version = "0.1.0";
+  };
+}
+"#,
+        )?;
+
+        let version_replacement = VersionReplacement {
+            old_version: "0.1.0".to_string(),
+            new_version: "0.2.0".to_string(),
+        };
+
+        let config = NixConfig {
+            files: vec!["default.nix".into()],
+            pnames: vec!["package1".to_string(), "package2".to_string()],
+        };
+        let mut replacers = nix::Replacer::new(version_replacement, config)
+            .determine_replacements()?
+            .expect("replacers should be generated");
+
+        assert_eq!(replacers.len(), 1);
+        let replacer = replacers.pop().unwrap();
+        assert_eq!(jail.strip_path(&replacer.path)?, "default.nix");
+        let file_contents = std::fs::read_to_string(replacer.temp_file.path())?;
+        assert_eq!(
+            file_contents,
+            r#"{ pkgs ? import <nixpkgs> {} }:
+let
+  version = "0.2.0";
+in {
+  package1 = pkgs.stdenv.mkDerivation {
+    pname = "package1";
+    inherit version;
+    src = pkgs.fetchurl {
+      pname = "dependency";
+      version = "0.1.0";
+      url = "https://example.com/dependency-0.1.0.tar.gz";
+      hash = "sha256-AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=";
+    };
+  };
+
+  package2 = pkgs.rustPlatform.buildRustPackage {
+    pname = "package2";
+    version = "0.2.0"; # keep me
+    src = ./.;
+  };
+
+  other = pkgs.stdenv.mkDerivation {
+    pname = "other";
+    version = "0.1.0";
+  };
+}
+"#
+        );
+
+        Ok(())
+    });
+}
+
+/// Updates the chart's `appVersion` and the tags of container images in every document of a
+/// Kubernetes manifest, leaving everything else untouched
+#[test]
+#[allow(clippy::too_many_lines)]
+fn yaml_paths() {
+    Jail::expect_with(|jail| {
+        jail.create_file(
+            "bomp.ron",
+            r#"(
+                by_file: Some({
+                   "chart/Chart.yaml": ( yaml_paths: Some(["appVersion"]), ),
+                }),
+            )
+        "#,
+        )?;
+
+        jail.create_file(
+            "chart/Chart.yaml",
+            r#"apiVersion: v2
+name: app
+# the chart's own version is managed separately
+version: 0.1.0
+appVersion: "0.1.0" # the application version
+"#,
+        )?;
+
+        jail.create_file(
+            "deploy.yaml",
+            "apiVersion: apps/v1
+kind: Deployment
+spec:
+  template:
+    spec:
+      containers:
+        - name: app
+          image: registry.example.com:5000/app:0.1.0 # bumped
+        - name: sidecar
+          image: 'registry.example.com/sidecar:0.1.0@sha256:abcd'
+        - name: other
+          image: registry.example.com/other:0.0.9
+---
+apiVersion: apps/v1
+kind: Deployment
+spec:
+  template:
+    spec:
+      containers:
+        - name: worker
+          image: registry.example.com/app:0.1.0
+          env:
+            - name: VERSION
+              value: 0.1.0
+",
+        )?;
+
+        let config = Config::from_ron(&String::from("bomp.ron"))?;
+        let by_file = config.by_file.unwrap();
+        let value_paths = by_file[Path::new("chart/Chart.yaml")].rules()[0]
+            .yaml_paths
+            .clone()
+            .unwrap();
+
+        let mut replacers =
+            yaml::Replacer::new("chart/Chart.yaml".into(), "0.1.0", &value_paths, "0.2.0")?
+                .determine_replacements()?
+                .expect("replacers should be generated");
+        let file_contents = std::fs::read_to_string(replacers.pop().unwrap().temp_file.path())?;
+        assert_eq!(
+            file_contents,
+            r#"apiVersion: v2
+name: app
+# the chart's own version is managed separately
+version: 0.1.0
+appVersion: "0.2.0" # the application version
+"#
+        );
+
+        let mut replacers = yaml::Replacer::new(
+            "deploy.yaml".into(),
+            "0.1.0",
+            &["spec.template.spec.containers[*].image".to_string()],
+            "0.2.0",
+        )?
+        .determine_replacements()?
+        .expect("replacers should be generated");
+        let replacer = replacers.pop().unwrap();
+        assert_eq!(jail.strip_path(&replacer.path)?, "deploy.yaml");
+        let file_contents = std::fs::read_to_string(replacer.temp_file.path())?;
+        assert_eq!(
+            file_contents,
+            "apiVersion: apps/v1
+kind: Deployment
+spec:
+  template:
+    spec:
+      containers:
+        - name: app
+          image: registry.example.com:5000/app:0.2.0 # bumped
+        - name: sidecar
+          image: 'registry.example.com/sidecar:0.2.0@sha256:abcd'
+        - name: other
+          image: registry.example.com/other:0.0.9
+---
+apiVersion: apps/v1
+kind: Deployment
+spec:
+  template:
+    spec:
+      containers:
+        - name: worker
+          image: registry.example.com/app:0.2.0
+          env:
+            - name: VERSION
+              value: 0.1.0
+"
+        );
+
+        // a path that matches nothing is an error
+        let result = yaml::Replacer::new(
+            "deploy.yaml".into(),
+            "0.1.0",
+            &["spec.replicas".to_string()],
+            "0.2.0",
+        )?
+        .determine_replacements();
+        assert!(result.is_err());
+
+        Ok(())
+    });
+}
+
+#[test]
+fn yaml_path_parsing() {
+    use yaml::PathSegment::{Index, Key, Wildcard};
+
+    assert_eq!(
+        yaml::parse_path("spec.containers[*].image").unwrap(),
+        [
+            Key("spec".to_string()),
+            Key("containers".to_string()),
+            Wildcard,
+            Key("image".to_string())
+        ]
+    );
+    assert_eq!(
+        yaml::parse_path("[0][1].version").unwrap(),
+        [Index(0), Index(1), Key("version".to_string())]
+    );
+    assert!(yaml::parse_path("spec..image").is_err());
+    assert!(yaml::parse_path("containers[a]").is_err());
+    assert!(yaml::parse_path("containers[0").is_err());
+}
+
+#[test]
+#[allow(clippy::too_many_lines)]
+fn structured_selectors() {
+    Jail::expect_with(|jail| {
+        jail.create_file(
+            "bomp.ron",
+            r#"(
+                by_file: Some({
+                   "manifest.json": ( selector: Some(Json("/plugin/version")), ),
+                   "pyproject.toml": ( selector: Some(Toml("tool.\"my-app\".version")), ),
+                }),
+            )
+        "#,
+        )?;
+
+        jail.create_file(
+            "manifest.json",
+            r#"{
+  "version": "0.1.0",
+  "plugin": {
+    "dependencies": { "core": "0.1.0" },
+    "version": "0.1.0"
+  }
+}
+"#,
+        )?;
+
+        jail.create_file(
+            "pyproject.toml",
+            r#"[project]
+version = "0.1.0"
+
+[tool."my-app"]
+version = "0.1.0" # keep in sync
+"#,
+        )?;
+
+        jail.create_file(
+            "values.yaml",
+            "image:
+  tag: 0.1.0
+sidecars:
+  - tag: 0.1.0
+  - tag: 0.1.0
+",
+        )?;
+
+        let versions = VersionReplacement {
+            old_version: "0.1.0".to_string(),
+            new_version: "0.2.0".to_string(),
+        };
+        let config = Config::from_ron(&String::from("bomp.ron"))?;
+        let by_file = config.by_file.unwrap();
+
+        let json_selector = by_file[Path::new("manifest.json")].rules()[0]
+            .selector
+            .clone()
+            .unwrap();
+        assert_eq!(json_selector, Selector::Json("/plugin/version".to_string()));
+        let mut replacers =
+            selector::Replacer::new("manifest.json".into(), json_selector, versions.clone())
+                .determine_replacements()?
+                .expect("replacers should be generated");
+        let file_contents = std::fs::read_to_string(replacers.pop().unwrap().temp_file.path())?;
+        assert_eq!(
+            file_contents,
+            r#"{
+  "version": "0.1.0",
+  "plugin": {
+    "dependencies": { "core": "0.1.0" },
+    "version": "0.2.0"
+  }
+}
+"#
+        );
+
+        let toml_selector = by_file[Path::new("pyproject.toml")].rules()[0]
+            .selector
+            .clone()
+            .unwrap();
+        let mut replacers =
+            selector::Replacer::new("pyproject.toml".into(), toml_selector, versions.clone())
+                .determine_replacements()?
+                .expect("replacers should be generated");
+        let file_contents = std::fs::read_to_string(replacers.pop().unwrap().temp_file.path())?;
+        assert_eq!(
+            file_contents,
+            r#"[project]
+version = "0.1.0"
+
+[tool."my-app"]
+version = "0.2.0" # keep in sync
+"#
+        );
+
+        let mut replacers = selector::Replacer::new(
+            "values.yaml".into(),
+            Selector::Yaml("image.tag".to_string()),
+            versions.clone(),
+        )
+        .determine_replacements()?
+        .expect("replacers should be generated");
+        let file_contents = std::fs::read_to_string(replacers.pop().unwrap().temp_file.path())?;
+        assert_eq!(
+            file_contents,
+            "image:
+  tag: 0.2.0
+sidecars:
+  - tag: 0.1.0
+  - tag: 0.1.0
+"
+        );
+
+        // a selector must resolve to exactly one value that holds the old version
+        let result = selector::Replacer::new(
+            "manifest.json".into(),
+            Selector::Json("/plugin/name".to_string()),
+            versions.clone(),
+        )
+        .determine_replacements();
+        assert!(matches!(result, Err(Error::SelectorNotFound { .. })));
+
+        let result = selector::Replacer::new(
+            "pyproject.toml".into(),
+            Selector::Toml("tool.poetry.version".to_string()),
+            versions.clone(),
+        )
+        .determine_replacements();
+        assert!(matches!(result, Err(Error::SelectorNotFound { .. })));
+
+        let result = selector::Replacer::new(
+            "values.yaml".into(),
+            Selector::Yaml("sidecars[*].tag".to_string()),
+            versions.clone(),
+        )
+        .determine_replacements();
+        assert!(matches!(
+            result,
+            Err(Error::SelectorAmbiguous { count: 2, .. })
+        ));
+
+        let result = selector::Replacer::new(
+            "manifest.json".into(),
+            Selector::Json("/plugin".to_string()),
+            versions,
+        )
+        .determine_replacements();
+        assert!(matches!(result, Err(Error::SelectorValueMismatch { .. })));
+
+        Ok(())
+    });
+}
+
+#[test]
+fn json_pointer_parsing() {
+    assert_eq!(selector::parse_pointer("").unwrap(), Vec::<String>::new());
+    assert_eq!(
+        selector::parse_pointer("/packages/a~1b/m~0n").unwrap(),
+        ["packages", "a/b", "m~n"]
+    );
+    assert!(selector::parse_pointer("version").is_err());
+}
+
+#[test]
+fn regex_capture() {
+    Jail::expect_with(|jail| {
+        jail.create_file(
+            "bomp.ron",
+            r#"(
+                by_file: Some({
+                   "Dockerfile": ( regex: Some(( pattern: "LABEL version=\"(?P<version>[^\"]+)\"", verify: true )), ),
+                }),
+            )
+        "#,
+        )?;
+
+        jail.create_file(
+            "Dockerfile",
+            r#"FROM alpine:3.19
+ARG BASE_VERSION="0.1.0"
+LABEL version="0.1.0"
+"#,
+        )?;
+
+        let versions = VersionReplacement {
+            old_version: "0.1.0".to_string(),
+            new_version: "0.2.0".to_string(),
+        };
+        let config = Config::from_ron(&String::from("bomp.ron"))?;
+        let by_file = config.by_file.unwrap();
+        let regex = by_file[Path::new("Dockerfile")].rules()[0]
+            .regex
+            .clone()
+            .unwrap();
+        assert!(regex.verify);
+
+        let mut replacers = capture::Replacer::new("Dockerfile".into(), &regex, versions.clone())?
+            .determine_replacements()?
+            .expect("replacers should be generated");
+        let file_contents = std::fs::read_to_string(replacers.pop().unwrap().temp_file.path())?;
+        assert_eq!(
+            file_contents,
+            r#"FROM alpine:3.19
+ARG BASE_VERSION="0.1.0"
+LABEL version="0.2.0"
+"#
+        );
+
+        // a captured value other than the old version is only an error when verifying
+        let mut regex = RegexReplace {
+            pattern: "FROM alpine:(?P<version>.+)".to_string(),
+            verify: true,
+        };
+        let result = capture::Replacer::new("Dockerfile".into(), &regex, versions.clone())?
+            .determine_replacements();
+        assert!(matches!(result, Err(Error::CapturedValueMismatch { .. })));
+        regex.verify = false;
+        let result = capture::Replacer::new("Dockerfile".into(), &regex, versions.clone())?
+            .determine_replacements();
+        assert!(result.is_ok());
+
+        let regex = RegexReplace {
+            pattern: "LABEL version=\"([^\"]+)\"".to_string(),
+            verify: false,
+        };
+        let result = capture::Replacer::new("Dockerfile".into(), &regex, versions);
+        assert!(matches!(result, Err(Error::MissingCaptureGroup(_))));
+
+        Ok(())
+    });
+}
+
+#[test]
+fn glob_patterns() {
+    Jail::expect_with(|jail| {
+        jail.create_file(".gitignore", "/target\n")?;
+        jail.create_file("README.md", "0.1.0")?;
+        jail.create_file("crates/a/README.md", "0.1.0")?;
+        jail.create_file("crates/b/README.md", "0.1.0")?;
+        jail.create_file("crates/b/nested/README.md", "0.1.0")?;
+        jail.create_file("charts/app/Chart.yaml", "version: 0.1.0")?;
+        jail.create_file("target/package/Chart.yaml", "version: 0.1.0")?;
+
+        assert!(glob::is_pattern("crates/*/README.md"));
+        assert!(glob::is_pattern("**/Chart.yaml"));
+        assert!(!glob::is_pattern("crates/a/README.md"));
+
+        assert_eq!(
+            glob::expand(&["crates/*/README.md"])?,
+            [
+                Path::new("crates/a/README.md"),
+                Path::new("crates/b/README.md")
+            ]
+        );
+        // ignored files are skipped
+        assert_eq!(
+            glob::expand(&["**/Chart.yaml"])?,
+            [Path::new("charts/app/Chart.yaml")]
+        );
+        assert!(glob::expand(&["docs/*.md"])?.is_empty());
+
+        Ok(())
+    });
+}
+
+#[test]
+fn multiple_rules() {
+    Jail::expect_with(|jail| {
+        jail.create_file(
+            "bomp.ron",
+            r#"(
+                by_file: Some({
+                   "README.md": [
+                       ( regex: Some(( pattern: "badge/version-(?P<version>[^-]+)-blue" )), ),
+                       ( search_value: Some("install"), ),
+                   ],
+                   "Cargo.toml": (),
+                }),
+            )
+        "#,
+        )?;
+
+        jail.create_file(
+            "README.md",
+            "![version](https://img.shields.io/badge/version-0.1.0-blue)
+
+Supports configs written for 0.1.0 and later.
+
+To install:
+```
+cargo install app@0.1.0
+```
+",
+        )?;
+
+        let config = Config::from_ron(&String::from("bomp.ron"))?;
+        let by_file = config.by_file.unwrap();
+        assert_eq!(
+            by_file[Path::new("Cargo.toml")],
+            FileRules::Single(FileTableData::default())
+        );
+        let readme_rules = by_file[Path::new("README.md")].rules();
+        assert_eq!(readme_rules.len(), 2);
+
+        let versions = VersionReplacement {
+            old_version: "0.1.0".to_string(),
+            new_version: "0.2.0".to_string(),
+        };
+        let rules = vec![
+            rule(
+                &readme_rules[0],
+                capture::Replacer::new(
+                    "README.md".into(),
+                    readme_rules[0].regex.as_ref().unwrap(),
+                    versions.clone(),
+                )?,
+            ),
+            rule(
+                &readme_rules[1],
+                search::Replacer::new(
+                    "README.md".into(),
+                    "0.1.0",
+                    readme_rules[1].search_value.as_ref().unwrap(),
+                    "0.2.0",
+                )?,
+            ),
+        ];
+
+        let mut replacers = rules::Replacer::new("README.md".into(), rules)
+            .determine_replacements()?
+            .expect("replacers should be generated");
+        assert_eq!(replacers.len(), 1);
+        let file_contents = std::fs::read_to_string(replacers.pop().unwrap().temp_file.path())?;
+        assert_eq!(
+            file_contents,
+            "![version](https://img.shields.io/badge/version-0.2.0-blue)
+
+Supports configs written for 0.1.0 and later.
+
+To install:
+```
+cargo install app@0.2.0
+```
+"
+        );
+
+        // a rule that fails prevents the file from being replaced
+        let search_rule = FileTableData {
+            search_value: Some("uninstall".to_string()),
+            ..FileTableData::default()
+        };
+        let rules = vec![
+            rule(
+                &FileTableData::default(),
+                simple::Replacer::new("README.md".into(), "0.1.0", "0.2.0")?,
+            ),
+            rule(
+                &search_rule,
+                search::Replacer::new("README.md".into(), "0.1.0", "uninstall", "0.2.0")?,
+            ),
+        ];
+        let result = rules::Replacer::new("README.md".into(), rules).determine_replacements();
+        assert!(result.is_err());
+
+        Ok(())
+    });
+}
+
+/// Creates a rule the same way as the `by_file` entries of a config.
+fn rule(config: &FileTableData, replacer: impl ContentReplacer + 'static) -> rules::Rule {
+    rules::Rule {
+        description: config.to_string(),
+        expect: config.expectation(),
+        replacer: Box::new(replacer),
+    }
+}
+
+#[test]
+fn expected_replacement_counts() {
+    Jail::expect_with(|jail| {
+        jail.create_file(
+            "bomp.ron",
+            r#"(
+                by_file: Some({
+                   "install.sh": [
+                       ( expect: Some(exactly(2)), ),
+                       ( search_value: Some("checksum"), expect: Some(any), ),
+                   ],
+                   "README.md": ( expect: Some(at_least(1)), ),
+                }),
+            )
+        "#,
+        )?;
+
+        jail.create_file(
+            "install.sh",
+            "VERSION=0.1.0
+URL=https://example.com/app-0.1.0.tar.gz
+",
+        )?;
+
+        let config = Config::from_ron(&String::from("bomp.ron"))?;
+        let by_file = config.by_file.unwrap();
+        let install_rules = by_file[Path::new("install.sh")].rules();
+        assert_eq!(install_rules[0].expectation(), Expect::Exactly(2));
+        assert_eq!(install_rules[1].expectation(), Expect::Any);
+        assert_eq!(
+            by_file[Path::new("README.md")].rules()[0].expectation(),
+            Expect::AtLeast(1)
+        );
+
+        // rules without an explicit expectation depend on how targeted they are
+        assert_eq!(FileTableData::default().expectation(), Expect::Any);
+        let search_rule = FileTableData {
+            search_value: Some("checksum".to_string()),
+            ..FileTableData::default()
+        };
+        assert_eq!(search_rule.expectation(), Expect::AtLeast(1));
+
+        let simple = || simple::Replacer::new("install.sh".into(), "0.1.0", "0.2.0");
+        let search = || search::Replacer::new("install.sh".into(), "0.1.0", "checksum", "0.2.0");
+
+        let rules = vec![
+            rule(&install_rules[0], simple()?),
+            rule(&install_rules[1], search()?),
+        ];
+        let replacers = rules::Replacer::new("install.sh".into(), rules).determine_replacements();
+        assert!(replacers.is_ok());
+
+        // too many replacements
+        let exactly_one = FileTableData {
+            expect: Some(Expect::Exactly(1)),
+            ..FileTableData::default()
+        };
+        let rules = vec![rule(&exactly_one, simple()?)];
+        let result = rules::Replacer::new("install.sh".into(), rules).determine_replacements();
+        let Err(error) = result else {
+            panic!("expected the replacement count to be rejected");
+        };
+        assert_eq!(
+            error.to_string(),
+            "simple replacement replaced 2 values in install.sh, expected exactly 1"
+        );
+
+        // too few replacements
+        let rules = vec![rule(&search_rule, search()?)];
+        let result = rules::Replacer::new("install.sh".into(), rules).determine_replacements();
+        assert!(matches!(
+            result,
+            Err(Error::UnexpectedReplacementCount { count: 0, .. })
+        ));
+
+        Ok(())
+    });
+}
+
+#[test]
+fn prerelease_increments() {
+    let version = |version: &str| semver::Version::parse(version).unwrap();
+    let pre = |increment, identifier: &str| VersionIncrement::Prerelease {
+        increment: Box::new(increment),
+        identifier: identifier.to_string(),
+    };
+
+    // starting a pre-release increments the final version first
+    assert_eq!(
+        increment_version(version("1.2.3"), pre(VersionIncrement::Minor, "rc")),
+        version("1.3.0-rc.1")
+    );
+    assert_eq!(
+        increment_version(version("1.2.3"), pre(VersionIncrement::Patch, "alpha")),
+        version("1.2.4-alpha.1")
+    );
+
+    // advancing a pre-release keeps the version it precedes
+    assert_eq!(
+        increment_version(version("1.3.0-rc.1"), pre(VersionIncrement::Minor, "rc")),
+        version("1.3.0-rc.2")
+    );
+    assert_eq!(
+        increment_version(version("1.3.0-rc.9"), pre(VersionIncrement::Patch, "rc")),
+        version("1.3.0-rc.10")
+    );
+    assert_eq!(
+        increment_version(version("1.3.0-beta.2"), pre(VersionIncrement::Minor, "rc")),
+        version("1.3.0-rc.1")
+    );
+    // further numeric parts are ignored rather than reset
+    assert_eq!(
+        increment_version(version("1.3.0-rc.1.2"), pre(VersionIncrement::Minor, "rc")),
+        version("1.3.0-rc.2")
+    );
+    // unless the increment requires a higher version
+    assert_eq!(
+        increment_version(version("1.3.0-rc.2"), pre(VersionIncrement::Major, "rc")),
+        version("2.0.0-rc.1")
+    );
+    assert_eq!(
+        increment_version(version("1.3.1-rc.2"), pre(VersionIncrement::Minor, "rc")),
+        version("1.4.0-rc.1")
+    );
+
+    assert_eq!(
+        increment_version(version("1.3.0-rc.2"), VersionIncrement::Release),
+        version("1.3.0")
+    );
+    assert_eq!(
+        increment_version(version("1.3.0-rc.2"), VersionIncrement::Patch),
+        version("1.3.1")
+    );
+
+    // a pre-release that sorts below the current one is rejected
+    assert!(matches!(
+        VersionScheme::Semver
+            .increment(version("1.3.0-rc.1"), pre(VersionIncrement::Minor, "beta")),
+        Err(Error::PrereleaseRegression { .. })
+    ));
+    assert_eq!(
+        VersionScheme::Semver
+            .increment(version("1.3.0-beta.1"), pre(VersionIncrement::Minor, "rc"))
+            .unwrap(),
+        version("1.3.0-rc.1")
+    );
+}
+
+#[test]
+fn tag_ordering() {
+    let tag = |version: &str| Tag {
+        commit_id: gix::ObjectId::null(gix::hash::Kind::Sha1),
+        version: semver::Version::parse(version).unwrap(),
+        prefix_v: false,
+        scheme: VersionScheme::Semver,
+        template: None,
+    };
+
+    let mut tags = [
+        tag("1.0.0"),
+        tag("1.0.0-rc.10"),
+        tag("0.9.0"),
+        tag("1.0.0-rc.2"),
+        tag("1.0.0-beta.1"),
+    ];
+    tags.sort();
+    let versions = tags
+        .iter()
+        .map(|tag| tag.version.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        versions,
+        [
+            "0.9.0",
+            "1.0.0-beta.1",
+            "1.0.0-rc.2",
+            "1.0.0-rc.10",
+            "1.0.0"
+        ]
+    );
+}
+
+#[test]
+fn build_metadata_bump_from_tag() {
+    Jail::expect_with(|jail| {
+        let repo = init_repository(jail)?;
+        let commit = commit_directories(&repo, "feat: add the app", &[("app", "1")])?;
+        repo.tag_reference(
+            "v1.2.0+abc1234",
+            commit,
+            gix::refs::transaction::PreviousValue::Any,
+        )?;
+        jail.create_file("VERSION", "1.2.0\n")?;
+
+        let tag = get_latest_tag(&repo, &VersionScheme::Semver, None, TagSelection::Reachable)?
+            .expect("a tag should be found");
+        assert_eq!(tag.version(), "v1.2.0+abc1234");
+        let new_tag = tag.increment_version(VersionIncrement::Patch)?;
+        assert_eq!(new_tag.bare_version(), "1.2.1");
+
+        // only the tag holds the metadata, so the files hold the version without it
+        let tag_only = BuildMetadataConfig {
+            parts: vec![BuildMetadataPart::CommitHash],
+            files: false,
+            tag: true,
+            changelog: true,
+        };
+        assert_eq!(tag.file_version(Some(&tag_only)), "1.2.0");
+        assert_eq!(tag.file_version(None), "1.2.0");
+        let mut replacers = simple::Replacer::new(
+            "VERSION".into(),
+            &tag.file_version(Some(&tag_only)),
+            &new_tag.bare_version(),
+        )?
+        .determine_replacements()?
+        .expect("the version should be replaced");
+        let mut replaced = String::new();
+        replacers
+            .pop()
+            .unwrap()
+            .temp_file
+            .read_to_string(&mut replaced)?;
+        assert_eq!(replaced, "1.2.1\n");
+
+        // with the metadata written to files, it is part of the old version
+        let everywhere = BuildMetadataConfig {
+            files: true,
+            ..tag_only
+        };
+        assert_eq!(tag.file_version(Some(&everywhere)), "1.2.0+abc1234");
+
+        Ok(())
+    });
+}
+
+/// Bumps the patch version twice, stamping each release with the given build metadata, and returns
+/// the contents of the version file and the name of the tag after each bump.
+fn bump_twice_with_metadata(config: &BuildMetadataConfig) -> Vec<(String, String)> {
+    let mut results = Vec::new();
+    Jail::expect_with(|jail| {
+        let repo = init_repository(jail)?;
+        let commit = commit_directories(&repo, "feat: add the app", &[("app", "1")])?;
+        repo.tag_reference("v0.1.0", commit, gix::refs::transaction::PreviousValue::Any)?;
+        jail.create_file("VERSION", "0.1.0\n")?;
+
+        for build in ["7", "8"] {
+            let tag = get_latest_tag(&repo, &VersionScheme::Semver, None, TagSelection::Reachable)?
+                .expect("a tag should be found");
+            let new_tag = tag.increment_version(VersionIncrement::Patch)?;
+            let build = semver::BuildMetadata::new(build)?;
+            let stamped = |enabled: bool| {
+                if enabled {
+                    new_tag.with_build_metadata(build.clone())
+                } else {
+                    new_tag.clone()
+                }
+            };
+
+            let mut replacers = simple::Replacer::new(
+                "VERSION".into(),
+                &tag.file_version(Some(config)),
+                &stamped(config.files).bare_version(),
+            )?
+            .determine_replacements()?
+            .expect("the version should be replaced");
+            replacers.pop().unwrap().persist()?;
+            let commit =
+                commit_directories(&repo, "chore(version): bump", &[("app", build.as_str())])?;
+            let tag_name = stamped(config.tag).version();
+            repo.tag_reference(
+                &tag_name,
+                commit,
+                gix::refs::transaction::PreviousValue::Any,
+            )?;
+            results.push((std::fs::read_to_string("VERSION")?, tag_name));
+        }
+
+        Ok(())
+    });
+    results
+}
+
+#[test]
+fn build_metadata_two_bumps() {
+    let everywhere = BuildMetadataConfig {
+        parts: vec![BuildMetadataPart::CommitHash],
+        files: true,
+        tag: true,
+        changelog: true,
+    };
+    assert_eq!(
+        bump_twice_with_metadata(&everywhere),
+        [
+            ("0.1.1+7\n".to_string(), "v0.1.1+7".to_string()),
+            ("0.1.2+8\n".to_string(), "v0.1.2+8".to_string()),
+        ]
+    );
+
+    let tag_only = BuildMetadataConfig {
+        files: false,
+        ..everywhere
+    };
+    assert_eq!(
+        bump_twice_with_metadata(&tag_only),
+        [
+            ("0.1.1\n".to_string(), "v0.1.1+7".to_string()),
+            ("0.1.2\n".to_string(), "v0.1.2+8".to_string()),
+        ]
+    );
+
+    // the old version in files can't be found without the metadata in the tag
+    Jail::expect_with(|jail| {
+        jail.create_file(
+            "bomp.ron",
+            "(build_metadata: Some((parts: [CommitHash], tag: false)))",
+        )?;
+        let result = Config::from_ron(&String::from("bomp.ron"));
+        assert!(matches!(result, Err(Error::BuildMetadataFilesWithoutTag)));
+        Ok(())
+    });
+}
+
+#[test]
+fn build_metadata_stamping() {
+    Jail::expect_with(|jail| {
+        jail.create_file(
+            "bomp.ron",
+            r#"(
+                build_metadata: Some((
+                    parts: [Date, Env("BOMPER_TEST_BUILD_NUMBER")],
+                    files: false,
+                )),
+            )
+        "#,
+        )?;
+
+        let config = Config::from_ron(&String::from("bomp.ron"))?;
+        let metadata_config = config.build_metadata.unwrap();
+        assert_eq!(
+            metadata_config,
+            BuildMetadataConfig {
+                parts: vec![
+                    BuildMetadataPart::Date,
+                    BuildMetadataPart::Env("BOMPER_TEST_BUILD_NUMBER".to_string())
+                ],
+                files: false,
+                tag: true,
+                changelog: true,
+            }
+        );
+
+        let repo = gix::init(jail.directory()).map_err(anyhow::Error::from)?;
+        let result = build_metadata(&repo, &metadata_config.parts);
+        assert!(matches!(result, Err(Error::MissingEnvVar(_))));
+
+        std::env::set_var("BOMPER_TEST_BUILD_NUMBER", "build-42");
+        let metadata = build_metadata(&repo, &metadata_config.parts)?;
+        std::env::remove_var("BOMPER_TEST_BUILD_NUMBER");
+        let date = chrono::Local::now().format("%Y%m%d");
+        assert_eq!(metadata.as_str(), format!("{date}.build-42"));
+
+        let tag = Tag {
+            commit_id: gix::ObjectId::null(gix::hash::Kind::Sha1),
+            version: semver::Version::new(1, 2, 0),
+            prefix_v: true,
+            scheme: VersionScheme::Semver,
+            template: None,
+        };
+        assert_eq!(
+            tag.with_build_metadata(metadata).version(),
+            format!("v1.2.0+{date}.build-42")
+        );
+
+        // the metadata must consist of valid identifiers
+        std::env::set_var("BOMPER_TEST_BUILD_NUMBER", "build 42");
+        let result = build_metadata(&repo, &metadata_config.parts);
+        std::env::remove_var("BOMPER_TEST_BUILD_NUMBER");
+        assert!(result.is_err());
+
+        Ok(())
+    });
+}
+
+#[test]
+fn calendar_versions() {
+    let date = |year, month, day| chrono::NaiveDate::from_ymd_opt(year, month, day).unwrap();
+
+    let config: Config = ron::from_str(r#"(scheme: Some(Calver("YYYY.0M.PATCH")))"#).unwrap();
+    let Some(VersionScheme::Calver(format)) = config.scheme else {
+        panic!("expected a calendar versioning scheme");
+    };
+    assert_eq!(format.to_string(), "YYYY.0M.PATCH");
+
+    let version = format.parse_version("2024.01.3").unwrap();
+    assert_eq!(version, semver::Version::new(2024, 1, 3));
+    assert_eq!(format.format_version(&version), "2024.01.3");
+    let pre = format.parse_version("2024.01.3-rc.1").unwrap();
+    assert_eq!(format.format_version(&pre), "2024.01.3-rc.1");
+    // tags of other schemes are ignored
+    assert_eq!(format.parse_version("1.2.3"), None);
+    assert_eq!(format.parse_version("2024.13.0"), None);
+    assert_eq!(format.parse_version("2024.01"), None);
+
+    // the patch is incremented within a month, and reset when the month changes
+    let next = |version: &str, today| {
+        let version = format.parse_version(version).unwrap();
+        format.format_version(&format.next_version(&version, today).unwrap())
+    };
+    assert_eq!(next("2024.01.3", date(2024, 1, 31)), "2024.01.4");
+    assert_eq!(next("2024.01.3", date(2024, 2, 1)), "2024.02.0");
+    assert_eq!(next("2024.12.3", date(2025, 1, 1)), "2025.01.0");
+
+    let increment = |version: &str, increment| {
+        let version = format.parse_version(version).unwrap();
+        let version = format.increment(version, increment, date(2024, 2, 1));
+        format.format_version(&version.unwrap())
+    };
+    assert_eq!(increment("2024.01.3", VersionIncrement::Major), "2024.02.0");
+    let rc = || VersionIncrement::Prerelease {
+        increment: Box::new(VersionIncrement::Patch),
+        identifier: "rc".to_string(),
+    };
+    assert_eq!(increment("2024.01.3", rc()), "2024.02.0-rc.1");
+    assert_eq!(increment("2024.02.0-rc.1", rc()), "2024.02.0-rc.2");
+    assert_eq!(
+        increment("2024.02.0-rc.2", VersionIncrement::Release),
+        "2024.02.0"
+    );
+
+    // without a patch, only one release can be made per period
+    let format: calver::Format = "0Y.0M.0D".parse().unwrap();
+    let version = format.parse_version("24.02.01").unwrap();
+    assert_eq!(
+        format.format_version(&format.next_version(&version, date(2024, 2, 9)).unwrap()),
+        "24.02.09"
+    );
+    assert!(matches!(
+        format.next_version(&version, date(2024, 2, 1)),
+        Err(Error::CalverExhausted(_))
+    ));
+
+    assert!("MM.YYYY".parse::<calver::Format>().is_err());
+    assert!("YYYY.PATCH.MM".parse::<calver::Format>().is_err());
+    assert!("YYYY.MM.DD.PATCH".parse::<calver::Format>().is_err());
+    assert!("YYYY.QQ".parse::<calver::Format>().is_err());
+}
+
+#[test]
+fn tag_templates() {
+    Jail::expect_with(|jail| {
+        jail.create_file(
+            "bomp.ron",
+            r#"(
+                tag_template: Some("{package}@{version}"),
+                package: Some("app"),
+            )
+        "#,
+        )?;
+
+        let config = Config::from_ron(&String::from("bomp.ron"))?;
+        let template = config.tag_template()?.unwrap();
+        assert_eq!(template.render("1.2.0"), "app@1.2.0");
+        assert_eq!(template.version_of("app@1.2.0"), Some("1.2.0"));
+        assert_eq!(template.version_of("other@1.2.0"), None);
+        assert_eq!(template.version_of("1.2.0"), None);
+
+        let template = TagTemplate::parse("{package}/v{version}", Some("crates/core"))?;
+        assert_eq!(template.render("0.3.1"), "crates/core/v0.3.1");
+        assert_eq!(template.version_of("crates/core/v0.3.1"), Some("0.3.1"));
+
+        // tags matching the template are found, skipping those that don't hold a version
+        let repo = init_repository(jail)?;
+        let tree = repo.empty_tree().id;
+        let commit_id = repo
+            .commit("HEAD", "initial commit", tree, gix::commit::NO_PARENT_IDS)
+            .map_err(anyhow::Error::from)?;
+        for name in [
+            "app@1.0.0",
+            "app@1.1.0",
+            "app@latest",
+            "other@2.0.0",
+            "3.0.0",
+        ] {
+            repo.tag_reference(name, commit_id, gix::refs::transaction::PreviousValue::Any)
+                .map_err(anyhow::Error::from)?;
+        }
+        let template = config.tag_template()?;
+        let tags = Tag::get_version_tags(
+            &repo,
+            &VersionScheme::Semver,
+            template.as_ref(),
+            TagSelection::Reachable,
+        )?;
+        let names = tags.iter().map(Tag::version).collect::<Vec<_>>();
+        assert_eq!(names, ["app@1.0.0", "app@1.1.0"]);
+
+        let tags =
+            Tag::get_version_tags(&repo, &VersionScheme::Semver, None, TagSelection::Reachable)?;
+        let names = tags.iter().map(Tag::version).collect::<Vec<_>>();
+        assert_eq!(names, ["3.0.0"]);
+
+        assert!(matches!(
+            TagTemplate::parse("{package}@{version}", None),
+            Err(Error::MissingTagPackage(_))
+        ));
+        for invalid in [
+            "release",
+            "{version}-{version}",
+            "{name}@{version}",
+            "v{version",
+        ] {
+            assert!(matches!(
+                TagTemplate::parse(invalid, Some("app")),
+                Err(Error::InvalidTagTemplate(_))
+            ));
+        }
+
+        Ok(())
+    });
+}
+
+/// Creates a git repository in the jail with an author identity, so that commits can be made.
+fn init_repository(jail: &Jail) -> Result<gix::Repository, Error> {
+    gix::init(jail.directory()).map_err(anyhow::Error::from)?;
+    let mut git_config = std::fs::OpenOptions::new()
+        .append(true)
+        .open(jail.directory().join(".git/config"))?;
+    writeln!(
+        git_config,
+        "[user]\n\tname = bomper\n\temail = bomper@example.com"
+    )?;
+    Ok(gix::open(jail.directory()).map_err(anyhow::Error::from)?)
+}
+
+/// Commits a snapshot of the repository on top of HEAD, in which each of the given directories
+/// holds a single file with the given contents.
+fn commit_directories(
+    repo: &gix::Repository,
+    message: &str,
+    directories: &[(&str, &str)],
+) -> Result<gix::ObjectId, Error> {
+    let tree = write_directories(repo, directories)?;
+    let parents = repo.head_id().ok().map(gix::Id::detach);
+    Ok(repo.commit("HEAD", message, tree, parents)?.detach())
+}
+
+/// Writes a tree in which each of the given directories holds a single file.
+fn write_directories(
+    repo: &gix::Repository,
+    directories: &[(&str, &str)],
+) -> Result<gix::ObjectId, Error> {
+    use gix::objs::tree::{Entry, EntryKind};
+
+    let mut entries = Vec::new();
+    for (directory, contents) in directories {
+        let blob = repo.write_blob(contents).map_err(anyhow::Error::from)?;
+        let tree = gix::objs::Tree {
+            entries: vec![Entry {
+                mode: EntryKind::Blob.into(),
+                filename: "file".into(),
+                oid: blob.detach(),
+            }],
+        };
+        entries.push(Entry {
+            mode: EntryKind::Tree.into(),
+            filename: (*directory).into(),
+            oid: repo.write_object(&tree)?.detach(),
+        });
+    }
+    entries.sort();
+    Ok(repo.write_object(&gix::objs::Tree { entries })?.detach())
+}
+
+#[test]
+fn path_filtered_commits() {
+    Jail::expect_with(|jail| {
+        let repo = init_repository(jail)?;
+        let root = commit_directories(
+            &repo,
+            "chore: initial commit",
+            &[("app", "1"), ("lib", "1")],
+        )?;
+        commit_directories(&repo, "feat: change the app", &[("app", "2"), ("lib", "1")])?;
+        commit_directories(&repo, "fix: change the lib", &[("app", "2"), ("lib", "2")])?;
+        let both = commit_directories(&repo, "feat!: change both", &[("app", "3"), ("lib", "3")])?;
+        commit_directories(
+            &repo,
+            "docs: add docs",
+            &[("app", "3"), ("docs", "1"), ("lib", "3")],
+        )?;
+
+        let tag = |commit_id| Tag {
+            commit_id,
+            version: semver::Version::new(0, 1, 0),
+            prefix_v: false,
+            scheme: VersionScheme::Semver,
+            template: None,
+        };
+        let summaries = |paths: &[&str]| -> Result<Vec<String>, Error> {
+            let paths = paths.iter().map(PathBuf::from).collect::<Vec<_>>();
+            let commits =
+                get_commits_since_tag(&repo, &tag(root), &paths, &CommitPolicy::default())?;
+            Ok(commits
+                .into_iter()
+                .map(|commit| commit.conventional_commit.summary)
+                .collect())
+        };
+
+        assert_eq!(summaries(&[])?.len(), 4);
+        assert_eq!(summaries(&["app"])?, ["change both", "change the app"]);
+        assert_eq!(summaries(&["lib"])?, ["change both", "change the lib"]);
+        assert_eq!(
+            summaries(&["docs", "lib"])?,
+            ["add docs", "change both", "change the lib"]
+        );
+        assert!(summaries(&["missing"])?.is_empty());
+
+        let commits = get_commits_between_tags(
+            &repo,
+            &tag(root),
+            &tag(both),
+            &["lib".into()],
+            &CommitPolicy::default(),
+        )?;
+        assert_eq!(commits.len(), 2);
+
+        Ok(())
+    });
+}
+
+#[test]
+fn commit_policies() {
+    Jail::expect_with(|jail| {
+        let repo = init_repository(jail)?;
+        let base = commit_directories(&repo, "feat: add the app", &[("app", "1")])?;
+        let side_tree = write_directories(&repo, &[("app", "2")])?;
+        let side = repo.commit("refs/heads/side", "fix: fix the app", side_tree, [base])?;
+        let main = commit_directories(&repo, "WIP", &[("app", "1"), ("docs", "1")])?;
+        let merge_tree = write_directories(&repo, &[("app", "2"), ("docs", "1")])?;
+        repo.commit(
+            "HEAD",
+            "Merge branch 'side'",
+            merge_tree,
+            [main, side.detach()],
+        )?;
+
+        let summaries = |non_conventional, merges| -> Result<Vec<String>, Error> {
+            let policy = CommitPolicy {
+                non_conventional,
+                merges,
+            };
+            let mut summaries = get_commits_since_initial_commit(&repo, &policy)?
+                .into_iter()
+                .map(|commit| {
+                    let commit = commit.conventional_commit;
+                    format!(
+                        "{}: {}",
+                        display_commit_type(&commit.commit_type),
+                        commit.summary
+                    )
+                })
+                .collect::<Vec<_>>();
+            summaries.sort();
+            Ok(summaries)
+        };
+
+        assert!(matches!(
+            summaries(NonConventionalCommits::Error, MergeCommits::Include),
+            Err(Error::NonConventionalCommit { .. })
+        ));
+        assert_eq!(
+            summaries(NonConventionalCommits::Skip, MergeCommits::Include)?,
+            ["bug fixes: fix the app", "features: add the app"]
+        );
+        assert_eq!(
+            summaries(NonConventionalCommits::Other, MergeCommits::Include)?,
+            [
+                "bug fixes: fix the app",
+                "features: add the app",
+                "other: Merge branch 'side'",
+                "other: WIP"
+            ]
+        );
+        assert_eq!(
+            summaries(NonConventionalCommits::Other, MergeCommits::Skip)?,
+            [
+                "bug fixes: fix the app",
+                "features: add the app",
+                "other: WIP"
+            ]
+        );
+        assert_eq!(
+            summaries(NonConventionalCommits::Other, MergeCommits::FirstParent)?,
+            [
+                "features: add the app",
+                "other: Merge branch 'side'",
+                "other: WIP"
+            ]
+        );
+
+        let config: Config =
+            ron::from_str("(commits: (non_conventional: Skip, merges: FirstParent))").unwrap();
+        assert_eq!(
+            config.commits,
+            CommitPolicy {
+                non_conventional: NonConventionalCommits::Skip,
+                merges: MergeCommits::FirstParent,
+            }
+        );
+
+        Ok(())
+    });
+}
+
+#[test]
+fn annotated_tags() {
+    Jail::expect_with(|jail| {
+        let repo = init_repository(jail)?;
+        let initial = commit_directories(&repo, "chore: initial commit", &[("app", "1")])?;
+        repo.tag(
+            "v0.1.0",
+            initial,
+            gix::object::Kind::Commit,
+            None,
+            "the first release",
+            gix::refs::transaction::PreviousValue::MustNotExist,
+        )?;
+        commit_directories(&repo, "feat: change the app", &[("app", "2")])?;
+
+        // the tag object is peeled to the commit it points to
+        let tags =
+            Tag::get_version_tags(&repo, &VersionScheme::Semver, None, TagSelection::Reachable)?;
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].commit_id, initial);
+        assert_eq!(tags[0].version(), "v0.1.0");
+
+        let commits = get_commits_since_tag(&repo, &tags[0], &[], &CommitPolicy::default())?;
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].conventional_commit.summary, "change the app");
+
+        let config: Config = ron::from_str("(annotated_tags: true)").unwrap();
+        assert!(config.annotated_tags);
+
+        // an annotated tag holds the message and the committer as its tagger
+        let head = repo.head_id()?.detach();
+        let entry = "## 0.2.0\n\n### features\n- change the app\n";
+        create_tag(&repo, "v0.2.0", head, Some(entry))?;
+        let object = repo
+            .find_reference("refs/tags/v0.2.0")
+            .map_err(anyhow::Error::from)?
+            .id()
+            .object()?;
+        assert_eq!(object.kind, gix::object::Kind::Tag);
+        let tag_object = object.into_tag();
+        let decoded = tag_object.decode()?;
+        assert_eq!(decoded.target(), head);
+        assert_eq!(decoded.message, entry);
+        let tagger = decoded.tagger.expect("the tag should have a tagger");
+        assert_eq!(tagger.name, "bomper");
+        assert_eq!(tagger.email, "bomper@example.com");
+
+        // a lightweight tag points at the commit directly
+        create_tag(&repo, "v0.2.1", head, None)?;
+        let object = repo
+            .find_reference("refs/tags/v0.2.1")
+            .map_err(anyhow::Error::from)?
+            .id()
+            .object()?;
+        assert_eq!(object.kind, gix::object::Kind::Commit);
+        assert_eq!(object.id, head);
+
+        // existing tags aren't replaced
+        assert!(create_tag(&repo, "v0.2.1", initial, None).is_err());
+
+        let tags =
+            Tag::get_version_tags(&repo, &VersionScheme::Semver, None, TagSelection::Reachable)?;
+        let versions = tags.iter().map(Tag::version).collect::<Vec<_>>();
+        assert_eq!(versions, ["v0.1.0", "v0.2.0", "v0.2.1"]);
+
+        Ok(())
+    });
+}
+
+#[test]
+fn reachable_tags() {
+    Jail::expect_with(|jail| {
+        let repo = init_repository(jail)?;
+        let tag = |name: &str, commit_id: gix::ObjectId| {
+            repo.tag_reference(name, commit_id, gix::refs::transaction::PreviousValue::Any)
+                .map(|_| ())
+        };
+        // HEAD is a maintenance branch of 1.x, while 2.0.0 was released on another branch
+        let first = commit_directories(&repo, "feat: add the app", &[("app", "1")])?;
+        tag("1.0.0", first)?;
+        let next_tree = write_directories(&repo, &[("app", "2")])?;
+        let next = repo.commit(
+            "refs/heads/next",
+            "feat!: rewrite the app",
+            next_tree,
+            [first],
+        )?;
+        tag("2.0.0", next.detach())?;
+        let fix = commit_directories(&repo, "fix: backport a fix", &[("app", "1.1")])?;
+
+        let versions = |selection| -> Result<Vec<String>, Error> {
+            let tags = Tag::get_version_tags(&repo, &VersionScheme::Semver, None, selection)?;
+            Ok(tags.iter().map(Tag::version).collect())
+        };
+        assert_eq!(versions(TagSelection::Reachable)?, ["1.0.0"]);
+        assert_eq!(versions(TagSelection::All)?, ["1.0.0", "2.0.0"]);
+
+        let latest = get_latest_tag(&repo, &VersionScheme::Semver, None, TagSelection::Reachable)?
+            .expect("a tag should be found");
+        assert_eq!(latest.version(), "1.0.0");
+        let commits = get_commits_since_tag(&repo, &latest, &[], &CommitPolicy::default())?;
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].commit_id, fix);
+
+        // after merging 2.0.0 into the branch, its commits are part of the next release, even though
+        // the tagged commit is the first parent of the merge
+        tag("1.0.1", fix)?;
+        let merge_tree = write_directories(&repo, &[("app", "2.1")])?;
+        repo.commit(
+            "HEAD",
+            "chore: merge 2.0.0",
+            merge_tree,
+            [fix, next.detach()],
+        )?;
+        let latest = get_latest_tag(&repo, &VersionScheme::Semver, None, TagSelection::Reachable)?
+            .expect("a tag should be found");
+        assert_eq!(latest.version(), "2.0.0");
+        let since_fix = Tag {
+            commit_id: fix,
+            version: semver::Version::new(1, 0, 1),
+            ..latest
+        };
+        let commits = get_commits_since_tag(&repo, &since_fix, &[], &CommitPolicy::default())?;
+        let summaries = commits
+            .iter()
+            .map(|commit| commit.conventional_commit.summary.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(summaries, ["merge 2.0.0", "rewrite the app"]);
+
+        Ok(())
+    });
+}
+
+#[test]
+fn increment_rules() {
+    let commits = |messages: &[&str]| {
+        messages
+            .iter()
+            .map(|message| conventional_commit_parser::parse(message).unwrap())
+            .collect::<Vec<_>>()
+    };
+    let increment = |messages: &[&str], version: &str, rules: &IncrementRules| {
+        let version = semver::Version::parse(version).unwrap();
+        determine_increment(&commits(messages), &version, rules)
+            .map(|increment| increment_version(version, increment).to_string())
+    };
+
+    // the defaults
+    let rules = IncrementRules::default();
+    assert_eq!(
+        increment(&["feat: a"], "1.2.3", &rules).as_deref(),
+        Some("1.3.0")
+    );
+    assert_eq!(
+        increment(&["chore: a"], "1.2.3", &rules).as_deref(),
+        Some("1.2.4")
+    );
+    assert_eq!(
+        increment(&["fix!: a"], "1.2.3", &rules).as_deref(),
+        Some("2.0.0")
+    );
+    assert_eq!(
+        increment(&["fix!: a"], "0.2.3", &rules).as_deref(),
+        Some("0.3.0")
+    );
+    assert_eq!(increment(&[], "1.2.3", &rules), None);
+
+    let config: Config = ron::from_str(
+        r#"(
+            increments: (
+                rules: [
+                    (commit_type: "perf", increment: Minor),
+                    (commit_type: "chore", scope: Some("deps"), increment: Patch),
+                    (commit_type: "chore", increment: None),
+                    (commit_type: "ci", increment: None),
+                    (commit_type: "docs", increment: None),
+                ],
+                default: Patch,
+                initial_development: false,
+            ),
+        )"#,
+    )
+    .unwrap();
+    let rules = config.increments;
+    assert_eq!(rules.rules.len(), 5);
+    assert_eq!(
+        increment(&["perf: a"], "1.2.3", &rules).as_deref(),
+        Some("1.3.0")
+    );
+    assert_eq!(
+        increment(&["fix: a"], "1.2.3", &rules).as_deref(),
+        Some("1.2.4")
+    );
+    assert_eq!(
+        increment(&["chore(deps): a"], "1.2.3", &rules).as_deref(),
+        Some("1.2.4")
+    );
+    assert_eq!(
+        increment(&["fix!: a"], "0.2.3", &rules).as_deref(),
+        Some("1.0.0")
+    );
+    // only commits that don't need a release
+    assert_eq!(
+        increment(&["chore: a", "ci: b", "docs: c"], "1.2.3", &rules),
+        None
+    );
+    assert_eq!(
+        increment(&["chore: a", "docs: c", "feat: d"], "1.2.3", &rules).as_deref(),
+        Some("1.3.0")
+    );
+    assert_eq!(
+        rules.increment_of(&commits(&["chore!: a"])[0]),
+        Increment::Major
+    );
+}
+
+#[test]
+fn changelog_sections() {
+    Jail::expect_with(|jail| {
+        let repo = init_repository(jail)?;
+        for (index, message) in [
+            "fix: fix the app",
+            "security: patch a vulnerability",
+            "feat: add a feature",
+            "chore(version): 1.0.0",
+            "deps: update dependencies",
+            "zeta: something else",
+            "chore: tidy up",
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            commit_directories(&repo, message, &[("app", &index.to_string())])?;
+        }
+        let commits = get_commits_since_initial_commit(&repo, &CommitPolicy::default())?;
+
+        let authors: Option<&std::collections::HashMap<String, String>> = None;
+        let render = |config: &ChangelogConfig| {
+            generate_changelog_entry(&repo, &commits, "1.1.0", None, None, authors, config)
+        };
+        let headings = |entry: &str| -> Vec<String> {
+            entry
+                .lines()
+                .filter_map(|line| line.strip_prefix("### "))
+                .map(ToString::to_string)
+                .collect()
+        };
+
+        // the default sections come first, and bomper's version commits are hidden
+        let entry = render(&ChangelogConfig::default())?;
+        assert!(!entry.contains("1.0.0"));
+        assert_eq!(
+            headings(&entry),
+            [
+                "features",
+                "bug fixes",
+                "chores",
+                "deps",
+                "security",
+                "zeta"
+            ]
+        );
+
+        let config: Config = ron::from_str(
+            r#"(
+                changelog: (
+                    sections: [
+                        (title: "Security", commit_types: ["security"]),
+                        (title: "Features", commit_types: ["feat"]),
+                        (title: "Fixes", commit_types: ["fix", "deps"]),
+                    ],
+                    hidden: [(commit_type: "chore")],
+                ),
+            )"#,
+        )
+        .unwrap();
+        let mut changelog = config.changelog;
+        let entry = render(&changelog)?;
+        assert!(!entry.contains("tidy up"));
+        assert_eq!(headings(&entry), ["Security", "Features", "Fixes", "zeta"]);
+
+        changelog.unlisted = UnlistedCommits::Hide;
+        let entry = render(&changelog)?;
+        assert!(entry.contains("update dependencies"));
+        assert!(!entry.contains("something else"));
+        assert_eq!(headings(&entry), ["Security", "Features", "Fixes"]);
+
+        Ok(())
+    });
+}
+
+#[test]
+fn changelog_templates() {
+    Jail::expect_with(|jail| {
+        let repo = init_repository(jail)?;
+        commit_directories(
+            &repo,
+            "feat(api)!: add a feature\n\nwith a body\n\nReviewed-by: someone",
+            &[("app", "1")],
+        )?;
+        commit_directories(&repo, "fix: fix the app", &[("app", "2")])?;
+        let commits = get_commits_since_initial_commit(&repo, &CommitPolicy::default())?;
+        let authors: Option<&std::collections::HashMap<String, String>> = None;
+        let render = |config: &ChangelogConfig| {
+            generate_changelog_entry(&repo, &commits, "1.0.0", None, None, authors, config)
+        };
+
+        jail.create_file(
+            "commit.md",
+            "{{ commit.type }}|{{ commit.scope }}|{{ commit.summary }}|{{ commit.body }}|\
+             {{ commit.breaking }}|{{ commit.short_hash|length }}|{{ commit.author }}\
+             {% for footer in commit.footers %}|{{ footer.token }}={{ footer.content }}{% endfor %}",
+        )?;
+        let mut config = ChangelogConfig {
+            commit_template: Some(PathBuf::from("commit.md")),
+            ..ChangelogConfig::default()
+        };
+        let entry = render(&config)?;
+        assert!(entry.starts_with("## 1.0.0 - "));
+        assert!(entry
+            .contains("- feat|api|add a feature|with a body|true|7|bomper|Reviewed-by=someone\n"));
+        assert!(entry.contains("- fix|none|fix the app|none|false|7|bomper\n"));
+
+        jail.create_file(
+            "entry.md",
+            "{{ entry.version }}\n\
+             {% for section in entry.sections %}{% for commit in section.commits %}\
+             {{ section.title }}: {% include \"changelog_commit\" %}\n\
+             {% endfor %}{% endfor %}",
+        )?;
+        config.template = Some(PathBuf::from("entry.md"));
+        config.commit_template = None;
+        let entry = render(&config)?;
+        let mut lines = entry.lines();
+        assert_eq!(lines.next(), Some("1.0.0"));
+        assert!(lines
+            .next()
+            .unwrap()
+            .starts_with("features: **(api)** add a feature - ("));
+        assert!(lines
+            .next()
+            .unwrap()
+            .starts_with("bug fixes: fix the app - ("));
+
+        config.template = Some(PathBuf::from("missing.md"));
+        assert!(matches!(
+            render(&config),
+            Err(Error::ChangelogTemplate { .. })
+        ));
+
+        Ok(())
+    });
+}
+
+#[test]
+fn changelog_breaking_changes() {
+    Jail::expect_with(|jail| {
+        let repo = init_repository(jail)?;
+        commit_directories(&repo, "feat: add a feature", &[("app", "1")])?;
+        commit_directories(
+            &repo,
+            "fix(config): rename an option\n\nBREAKING CHANGE: `old` is now `new`",
+            &[("app", "2")],
+        )?;
+        commit_directories(
+            &repo,
+            "chore!: drop support for old platforms",
+            &[("app", "3")],
+        )?;
+        let commits = get_commits_since_initial_commit(&repo, &CommitPolicy::default())?;
+
+        let authors: Option<&std::collections::HashMap<String, String>> = None;
+        let config = ChangelogConfig {
+            hidden: vec![CommitMatcher {
+                commit_type: "chore".to_string(),
+                scope: None,
+            }],
+            ..ChangelogConfig::default()
+        };
+        let entry =
+            generate_changelog_entry(&repo, &commits, "2.0.0", None, None, authors, &config)?;
+        let breaking = entry
+            .split("\n### ")
+            .find(|section| section.starts_with("breaking changes"))
+            .unwrap()
+            .lines()
+            .skip(1)
+            .filter(|line| !line.is_empty())
+            .map(|line| line.split(" - (").next().unwrap())
+            .collect::<Vec<_>>();
+        // the breaking changes come first, including those of hidden types
+        assert!(entry.find("### breaking changes") < entry.find("### features"));
+        assert_eq!(
+            breaking,
+            [
+                "- drop support for old platforms",
+                "- **(config)** rename an option",
+                "  `old` is now `new`",
+            ]
+        );
+        assert!(!entry.contains("### chores"));
+        assert!(entry.contains("### bug fixes\n- **(config)** rename an option"));
+
+        Ok(())
+    });
+}
+
+#[test]
+fn changelog_references() {
+    Jail::expect_with(|jail| {
+        init_repository(jail)?;
+        let mut git_config = std::fs::OpenOptions::new()
+            .append(true)
+            .open(jail.directory().join(".git/config"))?;
+        writeln!(
+            git_config,
+            "[remote \"origin\"]\n\turl = https://github.com/acme/app.git"
+        )?;
+        let repo = gix::open(jail.directory()).map_err(anyhow::Error::from)?;
+        commit_directories(
+            &repo,
+            "fix: handle empty lockfile (#42) for C#7 in JIRA-123\n\nCloses: #17",
+            &[("app", "1")],
+        )?;
+        let commits = get_commits_since_initial_commit(&repo, &CommitPolicy::default())?;
+
+        jail.create_file(
+            "commit.md",
+            "{{ commit.summary }}{% for footer in commit.footers %}|{{ footer.content }}{% endfor %}",
+        )?;
+        let authors: Option<&std::collections::HashMap<String, String>> = None;
+        let render = |config: &ChangelogConfig| -> Result<String, Error> {
+            let entry =
+                generate_changelog_entry(&repo, &commits, "1.0.0", None, None, authors, config)?;
+            Ok(entry
+                .lines()
+                .find_map(|line| line.strip_prefix("- "))
+                .unwrap()
+                .to_string())
+        };
+
+        // the heading links to the tag of the release, which may differ from the heading's version
+        let heading = |tag| -> Result<String, Error> {
+            let entry = generate_changelog_entry(
+                &repo,
+                &commits,
+                "1.0.0",
+                tag,
+                None,
+                authors,
+                &ChangelogConfig::default(),
+            )?;
+            Ok(entry.lines().next().unwrap().to_string())
+        };
+        assert!(heading(Some("v1.0.0+abc1234"))?
+            .starts_with("## [1.0.0](https://github.com/acme/app/releases/tag/v1.0.0+abc1234) - "));
+        assert!(heading(None)?.starts_with("## 1.0.0 - "));
+
+        let mut config = ChangelogConfig {
+            commit_template: Some(PathBuf::from("commit.md")),
+            ..ChangelogConfig::default()
+        };
+        assert_eq!(
+            render(&config)?,
+            "handle empty lockfile ([#42](https://github.com/acme/app/issues/42)) for C#7 in \
+             JIRA-123|[#17](https://github.com/acme/app/issues/17)"
+        );
+
+        config.references = vec![ReferencePattern {
+            pattern: r"\b([A-Z]+)-(\d+)\b".to_string(),
+            url: "https://jira.example.com/browse/$1-$2".to_string(),
+        }];
+        config.forge_references = false;
+        assert_eq!(
+            render(&config)?,
+            "handle empty lockfile (#42) for C#7 in \
+             [JIRA-123](https://jira.example.com/browse/JIRA-123)|#17"
+        );
+
+        config.references[0].pattern = "(".to_string();
+        assert!(matches!(render(&config), Err(Error::Regex(_))));
+
+        Ok(())
+    });
+}
+
+#[test]
+fn file_rule_validation() {
+    Jail::expect_with(|jail| {
+        let load = |rule: &str| {
+            jail.create_file(
+                "bomp.ron",
+                &format!("(by_file: Some({{\"Cargo.toml\": {rule}}}))"),
+            )?;
+            Config::from_ron(&String::from("bomp.ron"))
+        };
+
+        assert!(load("(selector: Some(Toml(\"package.version\")))").is_ok());
+        assert!(
+            load("(selector: Some(Toml(\"package.version\")), expect: Some(exactly(1)))").is_ok()
+        );
+
+        // a rule can only find the version in one way
+        let result = load(
+            "[(), (selector: Some(Toml(\"package.version\")), regex: Some((pattern: \"(?P<version>.*)\")))]",
+        );
+        match result {
+            Err(Error::ConflictingFileRule(fields)) => assert_eq!(fields, "`selector`, `regex`"),
+            result => panic!("expected conflicting modes, got {result:?}"),
+        }
+
+        // a selector always replaces exactly one value
+        for expect in ["exactly(2)", "at_least(2)", "any"] {
+            let result = load(&format!(
+                "(selector: Some(Toml(\"package.version\")), expect: Some({expect}))"
+            ));
+            assert!(matches!(result, Err(Error::SelectorExpectation { .. })));
+        }
+
+        Ok(())
+    });
+}
+
+#[test]
+fn cargo_independent_consecutive_bumps() {
+    Jail::expect_with(|jail| {
+        let repo = init_repository(jail)?;
+        let tag = |name: &str, commit_id: gix::ObjectId| {
+            repo.tag_reference(name, commit_id, gix::refs::transaction::PreviousValue::Any)
+                .map(|_| ())
+        };
+        let config = Config::default();
+        // the commits of each package since its latest release that require a new one
+        let releasing = |package: &str| -> Result<Vec<String>, Error> {
+            let template = TagTemplate::parse("{package}@{version}", Some(package))?;
+            let latest = get_latest_tag(
+                &repo,
+                &VersionScheme::Semver,
+                Some(&template),
+                TagSelection::Reachable,
+            )?
+            .expect("a tag should be found");
+            let mut commits =
+                get_commits_since_tag(&repo, &latest, &[PathBuf::from(package)], &config.commits)?;
+            commits.retain(|commit| !config.changelog.is_hidden(&commit.conventional_commit));
+            Ok(commits
+                .into_iter()
+                .map(|commit| commit.conventional_commit.summary)
+                .collect())
+        };
+
+        // `app` depends on `lib`
+        let first = commit_directories(
+            &repo,
+            "feat: add the packages",
+            &[("app", "lib = 0.3.0"), ("lib", "0.3.0")],
+        )?;
+        tag("app@1.4.0", first)?;
+        tag("lib@0.3.0", first)?;
+        commit_directories(
+            &repo,
+            "fix: fix the library",
+            &[("app", "lib = 0.3.0"), ("lib", "0.3.0, fixed")],
+        )?;
+        assert!(releasing("app")?.is_empty());
+        assert_eq!(releasing("lib")?, ["fix the library"]);
+
+        // releasing `lib` updates the requirement of `app`
+        let release = commit_directories(
+            &repo,
+            "chore(version): lib@0.3.1",
+            &[("app", "lib = 0.3.1"), ("lib", "0.3.1, fixed")],
+        )?;
+        tag("lib@0.3.1", release)?;
+
+        // the next bump has nothing to release
+        assert!(releasing("app")?.is_empty());
+        assert!(releasing("lib")?.is_empty());
+
+        Ok(())
+    });
+}
@@ -1,13 +1,95 @@
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+};
 
-use crate::error::Result;
+use crate::config::{
+    BuildMetadataConfig, BuildMetadataPart, CommitPolicy, Increment, IncrementRules, MergeCommits,
+    NonConventionalCommits, TagSelection,
+};
+use crate::error::{Error, Result};
 use conventional_commit_parser::commit::{CommitType, ConventionalCommit};
 
+pub mod calver;
+pub mod template;
+
+pub use template::TagTemplate;
+
+/// The type given to commits that aren't conventional commits, when they are included
+pub const OTHER_COMMIT_TYPE: &str = "other";
+
+/// How versions are written, and how they are incremented.
+/// Versions of every scheme are represented by a `semver::Version`, which determines their order.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VersionScheme {
+    /// semantic versioning, such as `1.2.3`
+    #[default]
+    Semver,
+    /// calendar versioning in the given format, such as `YYYY.0M.PATCH`
+    Calver(calver::Format),
+}
+
+impl VersionScheme {
+    /// Parses a version written in this scheme, returning `None` if it doesn't match.
+    #[must_use]
+    pub fn parse(&self, version: &str) -> Option<semver::Version> {
+        match self {
+            VersionScheme::Semver => semver::Version::parse(version).ok(),
+            VersionScheme::Calver(format) => format.parse_version(version),
+        }
+    }
+
+    /// Writes the version in this scheme.
+    #[must_use]
+    pub fn format(&self, version: &semver::Version) -> String {
+        match self {
+            VersionScheme::Semver => version.to_string(),
+            VersionScheme::Calver(format) => format.format_version(version),
+        }
+    }
+
+    /// Applies an increment to a version of this scheme.
+    /// Calendar versions are incremented based on the current local date.
+    ///
+    /// # Errors
+    ///
+    /// - the next calendar version can't be determined
+    /// - a pre-release would not follow the current version, such as `1.3.0-beta.1` after
+    ///   `1.3.0-rc.1`
+    pub fn increment(
+        &self,
+        version: semver::Version,
+        increment: VersionIncrement,
+    ) -> Result<semver::Version> {
+        let prerelease = matches!(increment, VersionIncrement::Prerelease { .. });
+        let current = version.clone();
+        let new = match self {
+            VersionScheme::Semver => increment_version(version, increment),
+            VersionScheme::Calver(format) => {
+                format.increment(version, increment, chrono::Local::now().date_naive())?
+            }
+        };
+
+        if prerelease && new.cmp_precedence(&current).is_le() {
+            return Err(Error::PrereleaseRegression {
+                current: self.format(&current),
+                new: self.format(&new),
+            });
+        }
+        Ok(new)
+    }
+}
+
 #[derive(Clone, Debug, Eq)]
 pub struct Tag {
     pub commit_id: gix::ObjectId,
     pub version: semver::Version,
     pub prefix_v: bool,
+    /// the scheme the version is written in
+    pub scheme: VersionScheme,
+    /// the template of the tag's name, which takes precedence over `prefix_v`
+    pub template: Option<TagTemplate>,
 }
 
 impl Ord for Tag {
@@ -18,7 +100,7 @@ impl Ord for Tag {
 
 impl PartialOrd<Tag> for Tag {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.version.cmp(&other.version))
+        Some(self.cmp(other))
     }
 }
 
@@ -29,56 +111,145 @@ impl PartialEq for Tag {
 }
 
 impl Tag {
-    /// Returns the version of the tag as a string.
+    /// Returns the version of the tag as a string, formatted as the tag's name.
     /// If the tag is prefixed with a 'v', the 'v' is included in the string.
     #[must_use]
     pub fn version(&self) -> String {
-        if self.prefix_v {
-            format!("v{}", self.version)
-        } else {
-            self.version.to_string()
+        match &self.template {
+            Some(template) => template.render(&self.bare_version()),
+            None if self.prefix_v => format!("v{}", self.bare_version()),
+            None => self.bare_version(),
+        }
+    }
+
+    /// Returns the version of the tag as a string, without any prefix.
+    #[must_use]
+    pub fn bare_version(&self) -> String {
+        self.scheme.format(&self.version)
+    }
+
+    /// Returns the version as the release of this tag wrote it to files.
+    /// Build metadata is only written to files when `build_metadata` enables it, so otherwise any
+    /// metadata of the tag is left out. It doesn't affect precedence, so the tag is still ordered
+    /// the same.
+    #[must_use]
+    pub fn file_version(&self, build_metadata: Option<&BuildMetadataConfig>) -> String {
+        if build_metadata.is_some_and(|config| config.files) {
+            return self.bare_version();
         }
+        let mut version = self.version.clone();
+        version.build = semver::BuildMetadata::EMPTY;
+        self.scheme.format(&version)
     }
 
     /// Create a new `Tag` with the version incremented by the given increment.
     /// Note: this does not change the commit id, only the version.
+    ///
+    /// # Errors
+    ///
+    /// - the version scheme is unable to increment the version
+    pub fn increment_version(&self, increment: VersionIncrement) -> Result<Self> {
+        let mut new = self.clone();
+        new.version = self.scheme.increment(self.version.clone(), increment)?;
+        Ok(new)
+    }
+
+    /// Create a new `Tag` with the given build metadata.
     #[must_use]
-    pub fn increment_version(&self, increment: VersionIncrement) -> Self {
+    pub fn with_build_metadata(&self, build: semver::BuildMetadata) -> Self {
         let mut new = self.clone();
-        new.version = increment_version(self.version.clone(), increment);
+        new.version.build = build;
         new
     }
 
+    /// Returns the tags that are versions of the given scheme, in ascending order.
+    /// With [`TagSelection::Reachable`], only tags of commits in the history of HEAD are included,
+    /// so that tags of other branches are ignored.
+    /// Pre-releases are ordered before their final release, e.g. `1.0.0-rc.2 < 1.0.0-rc.10 < 1.0.0`.
+    ///
+    /// Without a template, tags named `X.Y.Z` and `vX.Y.Z` are found. With a template, tags that
+    /// match it but don't hold a valid version are skipped with a warning.
+    ///
     /// # Errors
     ///
     /// - `gitoxide` is unable to read the repository references or tags
-    pub fn get_version_tags(repo: &gix::Repository) -> Result<Vec<Tag>> {
-        // TODO: should we only look for tags that are from the current branch?
+    /// - a tag can't be peeled to the object it points to
+    /// - `gitoxide` is unable to traverse the history of HEAD
+    pub fn get_version_tags(
+        repo: &gix::Repository,
+        scheme: &VersionScheme,
+        template: Option<&TagTemplate>,
+        selection: TagSelection,
+    ) -> Result<Vec<Tag>> {
         // TODO: should we ignore tags that are not semver?
+        let reachable = match selection {
+            // without a HEAD commit, no tags are reachable
+            TagSelection::Reachable => match repo.head_id() {
+                Ok(head) => Some(ancestor_ids(&head.object()?.into_commit())?),
+                Err(_) => Some(HashSet::new()),
+            },
+            TagSelection::All => None,
+        };
+
         let references = repo.references()?;
-        let tags = references.tags()?;
-        let tags = tags
-            .filter_map(|tag| {
-                let tag = tag.ok()?;
-                let name = tag.name().shorten().to_string();
-                let (version, prefix_v) = if let Some(stripped) = name.strip_prefix("v") {
-                    (semver::Version::parse(stripped).ok()?, true)
-                } else {
-                    (semver::Version::parse(&name).ok()?, false)
-                };
-                let commit_id = tag.id().into();
-                Some(Tag {
-                    commit_id,
-                    version,
-                    prefix_v,
-                })
-            })
-            .collect();
+        let mut tags = Vec::new();
+        for tag in references.tags()? {
+            let Ok(mut tag) = tag else {
+                continue;
+            };
+            let name = tag.name().shorten().to_string();
+            let Some((version, prefix_v)) = parse_tag_name(&name, scheme, template) else {
+                continue;
+            };
+
+            // annotated tags point to a tag object, which in turn points to the commit
+            let target = tag.peel_to_id_in_place()?;
+            if target.object()?.kind != gix::object::Kind::Commit {
+                tracing::warn!("ignoring tag `{name}`: it doesn't point to a commit");
+                continue;
+            }
+            if reachable
+                .as_ref()
+                .is_some_and(|reachable| !reachable.contains(&target.detach()))
+            {
+                continue;
+            }
+            tags.push(Tag {
+                commit_id: target.detach(),
+                version,
+                prefix_v,
+                scheme: scheme.clone(),
+                template: template.cloned(),
+            });
+        }
+        tags.sort();
 
         Ok(tags)
     }
 }
 
+/// Finds the version in a tag's name, along with whether it is prefixed with `v`.
+/// With a template, names that match it but don't hold a valid version are reported.
+fn parse_tag_name(
+    name: &str,
+    scheme: &VersionScheme,
+    template: Option<&TagTemplate>,
+) -> Option<(semver::Version, bool)> {
+    if let Some(template) = template {
+        let version = template.version_of(name)?;
+        let parsed = scheme.parse(version);
+        if parsed.is_none() {
+            tracing::warn!("ignoring tag `{name}`: `{version}` is not a valid version");
+        }
+        return parsed.map(|version| (version, false));
+    }
+
+    match name.strip_prefix("v") {
+        Some(stripped) => scheme.parse(stripped).map(|version| (version, true)),
+        None => scheme.parse(name).map(|version| (version, false)),
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Commit {
     pub commit_id: gix::ObjectId,
@@ -98,13 +269,90 @@ pub enum VersionIncrement {
     Major,
     Minor,
     Patch,
+    /// Starts or advances a pre-release with the given identifier, such as `rc`.
+    /// A final version is incremented first (`1.2.3` to `1.3.0-rc.1` for a minor increment),
+    /// while a pre-release keeps its version unless the increment requires a higher one
+    /// (`1.3.0-rc.1` to `1.3.0-rc.2`, or `1.3.0-beta.2` to `1.3.0-rc.1`).
+    Prerelease {
+        increment: Box<VersionIncrement>,
+        identifier: String,
+    },
+    /// Promotes a pre-release to its final version, such as `1.3.0-rc.2` to `1.3.0`
+    Release,
+}
+
+/// Creates build metadata from the given parts, joined with `.`.
+///
+/// # Errors
+///
+/// - git HEAD is not a commit
+/// - an environment variable is not set
+/// - a part is not a valid build metadata identifier
+pub fn build_metadata(
+    repo: &gix::Repository,
+    parts: &[BuildMetadataPart],
+) -> Result<semver::BuildMetadata> {
+    let identifiers = parts
+        .iter()
+        .map(|part| match part {
+            BuildMetadataPart::CommitHash => Ok(repo.head_id()?.to_hex_with_len(7).to_string()),
+            BuildMetadataPart::Date => Ok(chrono::Local::now().format("%Y%m%d").to_string()),
+            BuildMetadataPart::Env(name) => {
+                std::env::var(name).map_err(|_| Error::MissingEnvVar(name.clone()))
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(semver::BuildMetadata::new(&identifiers.join("."))?)
+}
+
+/// Tags the commit. With a message, an annotated tag is created, tagged by the repository's
+/// committer. Otherwise, a lightweight tag is created.
+///
+/// # Errors
+///
+/// - the tag already exists
+/// - the committer's identity can't be read from the git configuration
+/// - `gitoxide` is unable to write the tag object or reference
+pub fn create_tag(
+    repo: &gix::Repository,
+    name: &str,
+    commit: gix::ObjectId,
+    message: Option<&str>,
+) -> Result<()> {
+    let previous = gix::refs::transaction::PreviousValue::MustNotExist;
+    match message {
+        Some(message) => {
+            let tagger = repo.committer().transpose()?;
+            repo.tag(
+                name,
+                commit,
+                gix::object::Kind::Commit,
+                tagger,
+                message,
+                previous,
+            )?;
+        }
+        None => {
+            repo.tag_reference(name, commit, previous)?;
+        }
+    }
+
+    Ok(())
 }
 
 /// # Errors
 ///
 /// - `gitoxide` is unable to read the repository references or tags
-pub fn get_latest_tag(repo: &gix::Repository) -> Result<Option<Tag>> {
-    let tag = Tag::get_version_tags(repo)?.into_iter().max();
+pub fn get_latest_tag(
+    repo: &gix::Repository,
+    scheme: &VersionScheme,
+    template: Option<&TagTemplate>,
+    selection: TagSelection,
+) -> Result<Option<Tag>> {
+    let tag = Tag::get_version_tags(repo, scheme, template, selection)?
+        .into_iter()
+        .max();
     Ok(tag)
 }
 
@@ -113,9 +361,12 @@ pub fn get_latest_tag(repo: &gix::Repository) -> Result<Option<Tag>> {
 /// - `gitoxide` is unable to read the repository references or tags
 pub fn get_tags(
     repo: &gix::Repository,
+    scheme: &VersionScheme,
+    template: Option<&TagTemplate>,
+    selection: TagSelection,
     versions: &[semver::Version],
 ) -> Result<HashMap<semver::Version, Tag>> {
-    let tags = Tag::get_version_tags(repo)?;
+    let tags = Tag::get_version_tags(repo, scheme, template, selection)?;
     let tags = tags
         .into_iter()
         .filter(|tag| versions.contains(&tag.version))
@@ -124,37 +375,30 @@ pub fn get_tags(
     Ok(tags)
 }
 
+/// Retrieves the commits made since the tag.
+/// When `paths` is not empty, only commits that changed something within one of the paths
+/// (relative to the repository root) are included.
+/// Merge commits and commits that aren't conventional commits are handled according to `policy`.
+///
 /// # Errors
 ///
 /// - the repository has no commits
 /// - git HEAD is not a commit
 /// - `gitoxide` is unable to traverse the commit history
-pub fn get_commits_since_tag(repo: &gix::Repository, tag: &Tag) -> Result<Vec<Commit>> {
-    let head = repo.head_commit()?;
-    let ancestors = head.ancestors();
-    let mut parsed_commits = Vec::new();
-    for commit in ancestors.all()? {
-        let commit = commit?;
-        let object = commit.object()?;
-        if commit.id() == tag.commit_id {
-            break;
-        }
-        let message = object.message()?;
-        let mut full_message = String::new();
-        full_message.push_str(message.title.to_string().trim());
-        if let Some(body) = message.body {
-            full_message.push_str("\n\n");
-            full_message.push_str(&body.to_string());
-        }
-        let parsed = conventional_commit_parser::parse(&full_message)?;
-        parsed_commits.push(Commit {
-            commit_id: commit.id().into(),
-            conventional_commit: parsed,
-            signature: object.author()?.into(),
-        });
-    }
-
-    Ok(parsed_commits)
+/// - a commit is not a conventional commit, and the policy is to fail
+pub fn get_commits_since_tag(
+    repo: &gix::Repository,
+    tag: &Tag,
+    paths: &[PathBuf],
+    policy: &CommitPolicy,
+) -> Result<Vec<Commit>> {
+    collect_commits(
+        repo,
+        &repo.head_commit()?,
+        Some(tag.commit_id),
+        paths,
+        policy,
+    )
 }
 
 /// # Errors
@@ -162,52 +406,67 @@ pub fn get_commits_since_tag(repo: &gix::Repository, tag: &Tag) -> Result<Vec<Co
 /// - the repository has no commits
 /// - git HEAD is not a commit
 /// - `gitoxide` is unable to traverse the commit history
-/// - a commit message is found that is not a valid conventional commit
-pub fn get_commits_since_initial_commit(repo: &gix::Repository) -> Result<Vec<Commit>> {
-    let head = repo.head_commit()?;
-    let ancestors = head.ancestors();
-    let mut parsed_commits = Vec::new();
-    for commit in ancestors.all()? {
-        let commit = commit?;
-        let object = commit.object()?;
-        let message = object.message()?;
-        let mut full_message = String::new();
-        full_message.push_str(message.title.to_string().trim());
-        if let Some(body) = message.body {
-            full_message.push_str("\n\n");
-            full_message.push_str(&body.to_string());
-        }
-        let parsed = conventional_commit_parser::parse(&full_message)?;
-        parsed_commits.push(Commit {
-            commit_id: commit.id().into(),
-            conventional_commit: parsed,
-            signature: object.author()?.into(),
-        });
-    }
-
-    Ok(parsed_commits)
+/// - a commit is not a conventional commit, and the policy is to fail
+pub fn get_commits_since_initial_commit(
+    repo: &gix::Repository,
+    policy: &CommitPolicy,
+) -> Result<Vec<Commit>> {
+    collect_commits(repo, &repo.head_commit()?, None, &[], policy)
 }
 
+/// Retrieves the commits made between two tags, filtered by `paths` and `policy` in the same way
+/// as [`get_commits_since_tag`].
+///
 /// # Errors
 ///
 /// - the repository has no commits
 /// - git HEAD is not a commit
 /// - `gitoxide` is unable to traverse the commit history
-/// - a commit message is found that is not a valid conventional commit
+/// - a commit is not a conventional commit, and the policy is to fail
 pub fn get_commits_between_tags(
     repo: &gix::Repository,
     from: &Tag,
     to: &Tag,
+    paths: &[PathBuf],
+    policy: &CommitPolicy,
 ) -> Result<Vec<Commit>> {
     let start = repo.find_object(to.commit_id)?.into_commit();
-    let ancestors = start.ancestors();
+    collect_commits(repo, &start, Some(from.commit_id), paths, policy)
+}
+
+/// Walks the history from `start`, collecting the commits that changed one of the `paths` as
+/// conventional commits.
+/// The history of `stop` is left out entirely, so commits that were merged into both (such as a
+/// backported fix) aren't included again.
+fn collect_commits(
+    repo: &gix::Repository,
+    start: &gix::Commit<'_>,
+    stop: Option<gix::ObjectId>,
+    paths: &[PathBuf],
+    policy: &CommitPolicy,
+) -> Result<Vec<Commit>> {
+    let hidden = match stop {
+        Some(stop) => ancestor_ids(&repo.find_object(stop)?.into_commit())?,
+        None => HashSet::new(),
+    };
+    let mut ancestors = start.ancestors();
+    if policy.merges == MergeCommits::FirstParent {
+        ancestors = ancestors.first_parent_only();
+    }
+
     let mut parsed_commits = Vec::new();
-    for commit in ancestors.all()? {
+    let mut skipped = Vec::new();
+    for commit in ancestors.selected(move |id| !hidden.contains(id))? {
         let commit = commit?;
+        let commit_id: gix::ObjectId = commit.id().into();
         let object = commit.object()?;
-        if commit.id() == from.commit_id {
-            break;
+        if policy.merges == MergeCommits::Skip && object.parent_ids().count() > 1 {
+            continue;
         }
+        if !touches_paths(&object, paths)? {
+            continue;
+        }
+
         let message = object.message()?;
         let mut full_message = String::new();
         full_message.push_str(message.title.to_string().trim());
@@ -215,42 +474,120 @@ pub fn get_commits_between_tags(
             full_message.push_str("\n\n");
             full_message.push_str(&body.to_string());
         }
-        let parsed = conventional_commit_parser::parse(&full_message)?;
+        let parsed = match conventional_commit_parser::parse(&full_message) {
+            Ok(parsed) => parsed,
+            Err(source) => match policy.non_conventional {
+                NonConventionalCommits::Error => {
+                    return Err(Error::NonConventionalCommit {
+                        commit: commit_id,
+                        source,
+                    })
+                }
+                NonConventionalCommits::Skip => {
+                    skipped.push(commit_id.to_hex_with_len(7).to_string());
+                    continue;
+                }
+                NonConventionalCommits::Other => other_commit(&full_message),
+            },
+        };
         parsed_commits.push(Commit {
-            commit_id: commit.id().into(),
+            commit_id,
             conventional_commit: parsed,
             signature: object.author()?.into(),
         });
     }
 
+    if !skipped.is_empty() {
+        tracing::warn!(
+            "skipped commits that are not conventional commits: {}",
+            skipped.join(", ")
+        );
+    }
+
     Ok(parsed_commits)
 }
 
+/// Collects the ids of the commit and all of its ancestors.
+fn ancestor_ids(commit: &gix::Commit<'_>) -> Result<HashSet<gix::ObjectId>> {
+    let mut ids = HashSet::new();
+    for ancestor in commit.ancestors().all()? {
+        ids.insert(ancestor?.id);
+    }
+    Ok(ids)
+}
+
+/// Represents a commit that isn't a conventional commit as one of the type `other`, using the first
+/// line of its message as the summary.
+fn other_commit(message: &str) -> ConventionalCommit {
+    let (summary, body) = match message.split_once("\n\n") {
+        Some((summary, body)) => (summary, Some(body.trim().to_string())),
+        None => (message, None),
+    };
+    ConventionalCommit {
+        commit_type: CommitType::Custom(OTHER_COMMIT_TYPE.to_string()),
+        scope: None,
+        summary: summary.trim().to_string(),
+        body: body.filter(|body| !body.is_empty()),
+        footers: Vec::new(),
+        is_breaking_change: false,
+    }
+}
+
+/// Whether the commit changed something within one of the paths, compared to its first parent.
+/// Every commit matches when no paths are given, or when one of them is the repository root.
+fn touches_paths(commit: &gix::Commit<'_>, paths: &[PathBuf]) -> Result<bool> {
+    if paths.is_empty() || paths.iter().any(|path| path.as_os_str().is_empty()) {
+        return Ok(true);
+    }
+
+    let tree = commit.tree()?;
+    let parent_tree = match commit.parent_ids().next() {
+        Some(parent_id) => Some(parent_id.object()?.into_commit().tree()?),
+        None => None,
+    };
+    let mut buf = Vec::new();
+    for path in paths {
+        let current = tree
+            .lookup_entry_by_path(path, &mut buf)?
+            .map(|entry| entry.object_id());
+        let parent = match &parent_tree {
+            Some(parent_tree) => parent_tree
+                .lookup_entry_by_path(path, &mut buf)?
+                .map(|entry| entry.object_id()),
+            None => None,
+        };
+        if current != parent {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Determines the increment required by the commits, following the increment rules.
+/// Returns `None` when none of the commits require a release.
 pub fn determine_increment<'a, I: IntoIterator<Item = &'a ConventionalCommit>>(
     commits: I,
     current_version: &semver::Version,
-) -> VersionIncrement {
-    let (has_breaking, has_feature) =
-        commits
-            .into_iter()
-            .fold((false, false), |(has_breaking, has_feature), commit| {
-                (
-                    has_breaking || commit.is_breaking_change,
-                    has_feature || commit.commit_type == CommitType::Feature,
-                )
-            });
-    if has_breaking {
-        match current_version.major {
-            0 => VersionIncrement::Minor,
-            _ => VersionIncrement::Major,
+    rules: &IncrementRules,
+) -> Option<VersionIncrement> {
+    let increment = commits
+        .into_iter()
+        .map(|commit| rules.increment_of(commit))
+        .max()?;
+    match increment {
+        Increment::None => None,
+        Increment::Patch => Some(VersionIncrement::Patch),
+        Increment::Minor => Some(VersionIncrement::Minor),
+        Increment::Major if current_version.major == 0 && rules.initial_development => {
+            Some(VersionIncrement::Minor)
         }
-    } else if has_feature {
-        VersionIncrement::Minor
-    } else {
-        VersionIncrement::Patch
+        Increment::Major => Some(VersionIncrement::Major),
     }
 }
 
+/// # Panics
+///
+/// - the identifier of a pre-release increment is not a valid semver pre-release identifier
 #[must_use]
 pub fn increment_version(
     mut version: semver::Version,
@@ -279,5 +616,64 @@ pub fn increment_version(
             version.pre = semver::Prerelease::EMPTY;
             version
         }
+        VersionIncrement::Prerelease {
+            increment,
+            identifier,
+        } => {
+            if version.pre.is_empty() {
+                return with_prerelease(increment_version(version, *increment), None, &identifier);
+            }
+
+            // the pre-release already precedes a release that satisfies the increment
+            let satisfied = match *increment {
+                VersionIncrement::Major => version.minor == 0 && version.patch == 0,
+                VersionIncrement::Minor => version.patch == 0,
+                _ => true,
+            };
+            if satisfied {
+                let current = version.pre.clone();
+                with_prerelease(version, Some(&current), &identifier)
+            } else {
+                with_prerelease(increment_version(version, *increment), None, &identifier)
+            }
+        }
+        VersionIncrement::Release => {
+            version.build = semver::BuildMetadata::EMPTY;
+            version.pre = semver::Prerelease::EMPTY;
+            version
+        }
+    }
+}
+
+/// Sets the pre-release of the version to `<identifier>.N`.
+/// `N` advances the current pre-release if it has the same identifier, and starts at 1 otherwise.
+///
+/// # Panics
+///
+/// - the identifier is not a valid semver pre-release identifier
+fn with_prerelease(
+    mut version: semver::Version,
+    current: Option<&semver::Prerelease>,
+    identifier: &str,
+) -> semver::Version {
+    let number = current
+        .and_then(|current| prerelease_number(current, identifier))
+        .map_or(1, |number| number + 1);
+
+    version.build = semver::BuildMetadata::EMPTY;
+    version.pre = semver::Prerelease::new(&format!("{identifier}.{number}"))
+        .expect("pre-release identifiers are validated before incrementing");
+    version
+}
+
+/// Returns `N` from a pre-release of the form `<identifier>.N`, or `<identifier>.N.M` where the
+/// further parts are ignored, so that the next number still follows it.
+fn prerelease_number(pre: &semver::Prerelease, identifier: &str) -> Option<u64> {
+    let (current, numbers) = pre.as_str().split_once('.')?;
+    if current != identifier {
+        return None;
     }
+
+    let number = numbers.split('.').next()?;
+    number.parse().ok()
 }
@@ -0,0 +1,306 @@
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+use super::{increment_version, with_prerelease, VersionIncrement};
+use crate::error::{Error, Result};
+
+/// A component of a calendar version, following the conventions of <https://calver.org>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Token {
+    /// `YYYY`: the full year, such as `2024`
+    FullYear,
+    /// `YY`: the year without its century, such as `24`
+    ShortYear,
+    /// `0Y`: the zero-padded year without its century, such as `04`
+    PaddedYear,
+    /// `MM`: the month, such as `1`
+    Month,
+    /// `0M`: the zero-padded month, such as `01`
+    PaddedMonth,
+    /// `WW`: the ISO week of the year, such as `6`
+    Week,
+    /// `0W`: the zero-padded ISO week of the year, such as `06`
+    PaddedWeek,
+    /// `DD`: the day of the month, such as `9`
+    Day,
+    /// `0D`: the zero-padded day of the month, such as `09`
+    PaddedDay,
+    /// `PATCH` or `MICRO`: a counter of the releases made within the same period
+    Patch,
+}
+
+impl Token {
+    fn parse(token: &str) -> Option<Self> {
+        let token = match token {
+            "YYYY" => Token::FullYear,
+            "YY" => Token::ShortYear,
+            "0Y" => Token::PaddedYear,
+            "MM" => Token::Month,
+            "0M" => Token::PaddedMonth,
+            "WW" => Token::Week,
+            "0W" => Token::PaddedWeek,
+            "DD" => Token::Day,
+            "0D" => Token::PaddedDay,
+            "PATCH" | "MICRO" => Token::Patch,
+            _ => return None,
+        };
+        Some(token)
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Token::FullYear => "YYYY",
+            Token::ShortYear => "YY",
+            Token::PaddedYear => "0Y",
+            Token::Month => "MM",
+            Token::PaddedMonth => "0M",
+            Token::Week => "WW",
+            Token::PaddedWeek => "0W",
+            Token::Day => "DD",
+            Token::PaddedDay => "0D",
+            Token::Patch => "PATCH",
+        }
+    }
+
+    fn is_year(self) -> bool {
+        matches!(self, Token::FullYear | Token::ShortYear | Token::PaddedYear)
+    }
+
+    /// The value of the token on the given date, or `None` for the patch.
+    fn date_value(self, date: NaiveDate) -> Option<u64> {
+        let value = match self {
+            Token::FullYear => date.year(),
+            Token::ShortYear | Token::PaddedYear => date.year() % 100,
+            Token::Month | Token::PaddedMonth => date.month().try_into().ok()?,
+            Token::Week | Token::PaddedWeek => date.iso_week().week().try_into().ok()?,
+            Token::Day | Token::PaddedDay => date.day().try_into().ok()?,
+            Token::Patch => return None,
+        };
+        value.try_into().ok()
+    }
+
+    /// Whether the value could have been produced by this token.
+    /// This keeps tags of other schemes, such as `1.2.3`, from being read as calendar versions.
+    fn is_valid(self, value: u64) -> bool {
+        match self {
+            Token::FullYear => (1000..=9999).contains(&value),
+            Token::ShortYear | Token::PaddedYear => value <= 99,
+            Token::Month | Token::PaddedMonth => (1..=12).contains(&value),
+            Token::Week | Token::PaddedWeek => (1..=53).contains(&value),
+            Token::Day | Token::PaddedDay => (1..=31).contains(&value),
+            Token::Patch => true,
+        }
+    }
+
+    fn format(self, value: u64) -> String {
+        match self {
+            Token::PaddedYear | Token::PaddedMonth | Token::PaddedWeek | Token::PaddedDay => {
+                format!("{value:02}")
+            }
+            _ => value.to_string(),
+        }
+    }
+}
+
+/// The format of a calendar version, such as `YYYY.0M.PATCH`.
+///
+/// A format consists of up to three tokens separated by `.`, starting with a year. `PATCH` may
+/// only be used as the last token, and is reset whenever the date portion of the version changes.
+/// The tokens are stored in the major, minor, and patch components of a semver version, which
+/// allows calendar versions to be ordered and incremented like any other version.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Format {
+    tokens: Vec<Token>,
+}
+
+impl Format {
+    /// Parses a version written in this format, such as `2024.01.3` or `2024.01.3-rc.1`.
+    /// Returns `None` if the version doesn't match the format.
+    #[must_use]
+    pub fn parse_version(&self, version: &str) -> Option<semver::Version> {
+        let (core, build) = match version.split_once('+') {
+            Some((core, build)) => (core, Some(build)),
+            None => (version, None),
+        };
+        let (core, pre) = match core.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (core, None),
+        };
+
+        let values = core
+            .split('.')
+            .map(|value| value.parse::<u64>().ok())
+            .collect::<Option<Vec<_>>>()?;
+        if values.len() != self.tokens.len() {
+            return None;
+        }
+        if !self
+            .tokens
+            .iter()
+            .zip(&values)
+            .all(|(token, value)| token.is_valid(*value))
+        {
+            return None;
+        }
+
+        let mut version = from_components(&values);
+        if let Some(pre) = pre {
+            version.pre = semver::Prerelease::new(pre).ok()?;
+        }
+        if let Some(build) = build {
+            version.build = semver::BuildMetadata::new(build).ok()?;
+        }
+        Some(version)
+    }
+
+    /// Writes the version in this format.
+    #[must_use]
+    pub fn format_version(&self, version: &semver::Version) -> String {
+        let components = components(version);
+        let mut formatted = self
+            .tokens
+            .iter()
+            .zip(components)
+            .map(|(token, value)| token.format(value))
+            .collect::<Vec<_>>()
+            .join(".");
+        if !version.pre.is_empty() {
+            formatted.push('-');
+            formatted.push_str(version.pre.as_str());
+        }
+        if !version.build.is_empty() {
+            formatted.push('+');
+            formatted.push_str(version.build.as_str());
+        }
+
+        formatted
+    }
+
+    /// Determines the version released on the given date following `current`.
+    /// The date portion is updated if the period has changed, resetting the patch. Otherwise, the
+    /// patch is incremented.
+    ///
+    /// # Errors
+    ///
+    /// - the period hasn't changed and the format has no `PATCH` to increment
+    pub fn next_version(
+        &self,
+        current: &semver::Version,
+        today: NaiveDate,
+    ) -> Result<semver::Version> {
+        let current_values = components(current);
+        let mut values = Vec::with_capacity(self.tokens.len());
+        let mut newer = false;
+        for (token, current_value) in self.tokens.iter().zip(current_values) {
+            match token.date_value(today) {
+                Some(value) => {
+                    newer |= values == current_values[..values.len()] && value > current_value;
+                    values.push(value);
+                }
+                None if newer => values.push(0),
+                None => values.push(current_value + 1),
+            }
+        }
+
+        if !newer {
+            if !self.tokens.contains(&Token::Patch) {
+                return Err(Error::CalverExhausted(self.format_version(current)));
+            }
+            // the current version is from this period, or from the future
+            let patch = values.len() - 1;
+            values[..patch].copy_from_slice(&current_values[..patch]);
+        }
+
+        Ok(from_components(&values))
+    }
+
+    /// Applies an increment to a calendar version.
+    /// Every kind of automatic increment moves to the next calendar version, since the version
+    /// doesn't depend on the kind of changes that were made.
+    ///
+    /// # Errors
+    ///
+    /// - the next version can't be determined, see [`Format::next_version`]
+    pub fn increment(
+        &self,
+        version: semver::Version,
+        increment: VersionIncrement,
+        today: NaiveDate,
+    ) -> Result<semver::Version> {
+        match increment {
+            VersionIncrement::Manual(_) | VersionIncrement::Release => {
+                Ok(increment_version(version, increment))
+            }
+            VersionIncrement::Prerelease { identifier, .. } => {
+                if version.pre.is_empty() {
+                    let next = self.next_version(&version, today)?;
+                    Ok(with_prerelease(next, None, &identifier))
+                } else {
+                    let pre = version.pre.clone();
+                    Ok(with_prerelease(version, Some(&pre), &identifier))
+                }
+            }
+            VersionIncrement::Major | VersionIncrement::Minor | VersionIncrement::Patch => {
+                self.next_version(&version, today)
+            }
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(format: &str) -> Result<Self> {
+        let invalid_format = || Error::InvalidCalverFormat(format.to_string());
+
+        let tokens = format
+            .split('.')
+            .map(Token::parse)
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(invalid_format)?;
+        let valid = (1..=3).contains(&tokens.len())
+            && tokens[0].is_year()
+            && !tokens[..tokens.len() - 1].contains(&Token::Patch);
+        if !valid {
+            return Err(invalid_format());
+        }
+
+        Ok(Self { tokens })
+    }
+}
+
+impl TryFrom<String> for Format {
+    type Error = Error;
+
+    fn try_from(format: String) -> Result<Self> {
+        format.parse()
+    }
+}
+
+impl From<Format> for String {
+    fn from(format: Format) -> Self {
+        format.to_string()
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let tokens = self
+            .tokens
+            .iter()
+            .map(|token| token.as_str())
+            .collect::<Vec<_>>();
+        write!(f, "{}", tokens.join("."))
+    }
+}
+
+fn components(version: &semver::Version) -> [u64; 3] {
+    [version.major, version.minor, version.patch]
+}
+
+fn from_components(values: &[u64]) -> semver::Version {
+    let value = |index: usize| values.get(index).copied().unwrap_or_default();
+    semver::Version::new(value(0), value(1), value(2))
+}
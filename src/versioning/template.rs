@@ -0,0 +1,64 @@
+use crate::error::{Error, Result};
+
+const VERSION: &str = "{version}";
+const PACKAGE: &str = "{package}";
+
+/// The name of version tags, such as `v{version}` or `{package}@{version}`.
+/// The template is used both to find the version of existing tags and to name new ones.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TagTemplate {
+    prefix: String,
+    suffix: String,
+}
+
+impl TagTemplate {
+    /// Parses a template, filling in `{package}` with the given package name.
+    ///
+    /// # Errors
+    ///
+    /// - the template doesn't contain `{version}` exactly once
+    /// - the template contains `{package}`, but no package was given
+    /// - the template contains an unknown placeholder
+    pub fn parse(template: &str, package: Option<&str>) -> Result<Self> {
+        let invalid_template = || Error::InvalidTagTemplate(template.to_string());
+
+        let (prefix, suffix) = template.split_once(VERSION).ok_or_else(invalid_template)?;
+        if suffix.contains(VERSION) {
+            return Err(invalid_template());
+        }
+
+        let fill = |part: &str| {
+            let filled = if part.contains(PACKAGE) {
+                let package =
+                    package.ok_or_else(|| Error::MissingTagPackage(template.to_string()))?;
+                part.replace(PACKAGE, package)
+            } else {
+                part.to_string()
+            };
+            if filled.contains(['{', '}']) {
+                return Err(invalid_template());
+            }
+            Ok(filled)
+        };
+
+        Ok(Self {
+            prefix: fill(prefix)?,
+            suffix: fill(suffix)?,
+        })
+    }
+
+    /// Creates the name of the tag for the given version.
+    #[must_use]
+    pub fn render(&self, version: &str) -> String {
+        format!("{}{version}{}", self.prefix, self.suffix)
+    }
+
+    /// Returns the version portion of a tag name, if the name matches the template.
+    #[must_use]
+    pub fn version_of<'a>(&self, name: &'a str) -> Option<&'a str> {
+        let version = name
+            .strip_prefix(&self.prefix)?
+            .strip_suffix(&self.suffix)?;
+        (!version.is_empty()).then_some(version)
+    }
+}